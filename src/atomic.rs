@@ -0,0 +1,177 @@
+//! Writing a set of files all or nothing. Every file is first written next
+//! to its destination under a temporary name, so the final rename never
+//! crosses filesystems, and only renamed into place once all of them were
+//! written.
+
+use crate::{parallel, platform};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+/// A file to create or overwrite.
+pub struct File<'a> {
+    pub path: &'a Path,
+    /// Permissions to set, if not the default (or those of the file it
+    /// replaces).
+    pub mode: Option<u32>,
+    pub contents: &'a str,
+}
+
+/// Writes every file in `files`, or none of them: after a failure the
+/// files replaced so far get their old contents back, and the temporary
+/// files, the files created so far and the directories created for them
+/// are removed again. Returns the files that didn't exist before.
+pub fn write_all(files: &[File]) -> Result<Vec<PathBuf>, String> {
+    let mut dirs = Vec::new();
+    for file in files {
+        if let Err(e) = create_parents(file.path, &mut dirs) {
+            remove_dirs(&dirs);
+            return Err(creating(file.path, &e));
+        }
+    }
+
+    // Write through symlinks rather than replacing them.
+    let targets: Vec<(&File, PathBuf)> =
+        files.iter().map(|file| (file, target(file.path))).collect();
+    let staged = parallel::map(&targets, |(file, target)| {
+        let temporary = temporary_path(target);
+        stage(file, &temporary)
+            .map(|()| temporary.clone())
+            .map_err(|e| {
+                let _ = fs::remove_file(&temporary);
+                creating(file.path, &e)
+            })
+    });
+    let temporaries: Vec<PathBuf> = staged.iter().flatten().cloned().collect();
+    if let Some(e) = staged.into_iter().find_map(Result::err) {
+        remove_files(&temporaries);
+        remove_dirs(&dirs);
+        return Err(e);
+    }
+
+    // Keep what is replaced until every file is in place.
+    let mut backups: Vec<Option<PathBuf>> = Vec::new();
+    for (file, target) in &targets {
+        let backup = match back_up(target) {
+            Ok(backup) => backup,
+            Err(e) => {
+                remove_files(&temporaries);
+                remove_files(backups.iter().flatten());
+                remove_dirs(&dirs);
+                return Err(creating(file.path, &e));
+            }
+        };
+        backups.push(backup);
+    }
+
+    for (index, (file, target)) in targets.iter().enumerate() {
+        if let Err(e) = fs::rename(&temporaries[index], target) {
+            for ((_, target), backup) in targets[..index].iter().zip(&backups) {
+                let _ = match backup {
+                    Some(backup) => fs::rename(backup, target),
+                    None => fs::remove_file(target),
+                };
+            }
+            remove_files(&temporaries[index..]);
+            remove_files(backups[index..].iter().flatten());
+            remove_dirs(&dirs);
+            return Err(creating(file.path, &e));
+        }
+    }
+    remove_files(backups.iter().flatten());
+
+    Ok(files
+        .iter()
+        .zip(&backups)
+        .filter(|(_, backup)| backup.is_none())
+        .map(|(file, _)| file.path.to_path_buf())
+        .collect())
+}
+
+fn creating(path: &Path, e: &io::Error) -> String {
+    format!("creating '{}': {e}", path.display())
+}
+
+/// Creates the missing parent directories of `path`, adding them to
+/// `created` innermost first.
+fn create_parents(path: &Path, created: &mut Vec<PathBuf>) -> io::Result<()> {
+    let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) else {
+        return Ok(());
+    };
+    let missing: Vec<PathBuf> = parent
+        .ancestors()
+        .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+        .map(Path::to_path_buf)
+        .collect();
+    fs::create_dir_all(parent)?;
+    created.splice(0..0, missing);
+    Ok(())
+}
+
+/// `path`, or the file it links to if it's a symlink.
+fn target(path: &Path) -> PathBuf {
+    let link = fs::symlink_metadata(path).is_ok_and(|metadata| metadata.is_symlink());
+    match link.then(|| fs::canonicalize(path)) {
+        Some(Ok(target)) => target,
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Links (or else copies) an existing `target` to a backup next to it,
+/// returning where. `None` if there's nothing to back up.
+fn back_up(target: &Path) -> io::Result<Option<PathBuf>> {
+    if !target.exists() {
+        return Ok(None);
+    }
+    if target.is_dir() {
+        return Err(io::ErrorKind::IsADirectory.into());
+    }
+    let backup = backup_path(target);
+    if fs::hard_link(target, &backup).is_err() {
+        fs::copy(target, &backup)?;
+    }
+    Ok(Some(backup))
+}
+
+fn remove_files<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) {
+    for path in paths {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Removes the directories in `dirs`, innermost first, if they are empty.
+fn remove_dirs(dirs: &[PathBuf]) {
+    for dir in dirs {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+/// `.<name>.tf-<pid>` next to `path`.
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.tf-{}", process::id()))
+}
+
+/// `.<name>.tf-<pid>.orig` next to `path`.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = temporary_path(path).into_os_string();
+    backup.push(".orig");
+    PathBuf::from(backup)
+}
+
+/// Writes `file` to `temporary` with its permissions.
+fn stage(file: &File, temporary: &Path) -> io::Result<()> {
+    fs::write(temporary, file.contents)?;
+    match file.mode {
+        Some(mode) => platform::set_mode(temporary, mode),
+        None => match fs::metadata(file.path) {
+            Ok(existing) => fs::set_permissions(temporary, existing.permissions()),
+            Err(_) => Ok(()),
+        },
+    }
+}
@@ -0,0 +1,328 @@
+use crate::{
+    cpp::Standard, date::Timezone, encoding::LineEnding, error::TfError, filetype::Kind,
+    guard::GuardStyle, license::LicenseStyle, modeline::Modeline,
+};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+use toml_edit::{DocumentMut, Item, Value};
+
+/// Name of `value` as written on the command line and in the config.
+pub fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Written by `tf config init`. Every key is commented out so the file
+/// documents the settings without changing any defaults.
+const DEFAULT_CONFIG: &str = r#"# tf configuration. Command line flags take precedence over these values,
+# and $TF_<KEY> environment variables (e.g. TF_DATE_FORMAT) over the file.
+
+# Author written into file headers. Checked after $TF_AUTHOR and before
+# `git config user.name` and $USER.
+# author = "Jane Doe"
+
+# SPDX license identifier added to every header.
+# license = "MIT"
+
+# "short" for only the SPDX line, "full" to include the license notice.
+# license_style = "short"
+
+# Directory of user templates (`<ext>.tera`) overriding the built-ins.
+# template_dir = "/path/to/templates"
+
+# Copyright line under the Purpose line, e.g. "Copyright (c) 2025 Acme Corp.",
+# with the year taken from the header date. `copyright_since` turns it into
+# a range (2019-2025), and `copyright_notice` adds lines below it.
+# copyright = "Acme Corp."
+# copyright_since = 2019
+# copyright_notice = "All rights reserved."
+
+# Header guards for C/C++ headers: "ifndef" or "pragma-once". Unset uses
+# #ifndef for .h and #pragma once for .hpp.
+# guard_style = "ifndef"
+
+# Guard macro naming: a project prefix, the whole path instead of only the
+# filename (src/uart.h -> SRC_UART_H), and a trailing underscore.
+# guard_prefix = "MYPROJ_"
+# guard_path = false
+# guard_trailing_underscore = false
+
+# C++ standard the C++ templates target ("c++11" to "c++23"; C++17 idioms
+# unless set), and a namespace C++ sources and headers are wrapped in.
+# cpp_std = "c++20"
+# namespace = "acme::uart"
+
+# Header date: "us" (01/31/2025), "iso" (2025-01-31) or a strftime format.
+# date_format = "us"
+
+# Timezone of dates and {{ datetime }}: "local" or "utc".
+# timezone = "local"
+
+# Append a "vim" or "emacs" modeline matching the file's indentation.
+# modeline = "vim"
+
+# Re-indent new files and set their final newline as the nearest
+# .editorconfig says.
+# editorconfig = true
+
+# Line endings of new files ("lf" or "crlf"), in general and by filetype,
+# and whether they start with a UTF-8 byte order mark. Flags and
+# .editorconfig win; files get LF and no BOM unless set.
+# line_endings = "lf"
+# filetype_line_endings = { bat = "crlf", ps1 = "crlf" }
+# bom = false
+
+# Open created files in $VISUAL/$EDITOR as if `--edit` was given.
+# auto_edit = false
+
+# Permissions of new files by filetype (or base filetype, so "py" covers
+# every Python flavor). Bash scripts are 0744 unless set here.
+# permissions = { py = "755", tcl = "755" }
+
+# Interpreter lines of scripts by filetype, and the `set` flags of Bash
+# scripts ("" for none). Bash uses /bin/bash and "eux" unless set here;
+# Python files only get a shebang if one is set.
+# shebang = { bash = "/usr/bin/env bash", py = "/usr/bin/env python3" }
+# set_flags = "eu"
+
+# The --kind used when none is given, by filetype, e.g. library sources
+# without main() for C and C++. `--kind main` still gets main().
+# default_kinds = { c = "lib", cpp = "lib" }
+
+# Extra extensions for existing filetypes, by template name or extension.
+# Aliases can also remap built-in extensions.
+# aliases = { cc = "cpp", hh = "hpp", zsh = "bash", pyw = "py" }
+"#;
+
+/// Settings read from `$XDG_CONFIG_HOME/tf/config.toml`. Every key is
+/// optional and command line flags take precedence.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub license_style: Option<LicenseStyle>,
+    /// Copyright holder, e.g. `Acme Corp.`.
+    pub copyright: Option<String>,
+    /// First year of the copyright range.
+    pub copyright_since: Option<i32>,
+    /// Lines under the copyright line, e.g. `All rights reserved.`.
+    pub copyright_notice: Option<String>,
+    pub template_dir: Option<PathBuf>,
+    pub guard_style: Option<GuardStyle>,
+    pub guard_prefix: Option<String>,
+    pub guard_path: Option<bool>,
+    pub guard_trailing_underscore: Option<bool>,
+    pub cpp_std: Option<Standard>,
+    /// Namespace of C++ sources and headers, e.g. `acme::uart`.
+    pub namespace: Option<String>,
+    /// `us`, `iso` or a strftime format.
+    pub date_format: Option<String>,
+    pub timezone: Option<Timezone>,
+    pub modeline: Option<Modeline>,
+    /// Follow `.editorconfig` files, `true` unless set.
+    pub editorconfig: Option<bool>,
+    pub line_endings: Option<LineEnding>,
+    /// Line endings by filetype name, e.g. `bat = "crlf"`.
+    pub filetype_line_endings: BTreeMap<String, LineEnding>,
+    /// Start files with a UTF-8 byte order mark.
+    pub bom: Option<bool>,
+    /// Open created files in the editor as if `--edit` was given.
+    pub auto_edit: Option<bool>,
+    /// Extra extensions mapped to existing filetypes, e.g. `cc = "cpp"`.
+    pub aliases: BTreeMap<String, String>,
+    /// Octal modes of new files by filetype name, e.g. `py = "755"`.
+    pub permissions: BTreeMap<String, String>,
+    /// Interpreters by filetype name, e.g. `bash = "/usr/bin/env bash"`.
+    pub shebang: BTreeMap<String, String>,
+    /// `set` flags of Bash scripts, e.g. `eu`.
+    pub set_flags: Option<String>,
+    /// The `--kind` used when none is given, by filetype name.
+    pub default_kinds: BTreeMap<String, Kind>,
+    /// Keys set by `$TF_<KEY>` rather than the file.
+    #[serde(skip)]
+    pub from_env: Vec<&'static str>,
+}
+
+/// Keys `$TF_<KEY>` environment variables override, e.g. `TF_DATE_FORMAT`
+/// for `date_format`. `$TF_AUTHOR` is checked with the other author sources.
+const ENV_KEYS: &[&str] = &[
+    "license",
+    "license_style",
+    "copyright",
+    "copyright_since",
+    "copyright_notice",
+    "template_dir",
+    "guard_style",
+    "guard_prefix",
+    "guard_path",
+    "guard_trailing_underscore",
+    "cpp_std",
+    "namespace",
+    "date_format",
+    "timezone",
+    "modeline",
+    "editorconfig",
+    "line_endings",
+    "filetype_line_endings",
+    "bom",
+    "auto_edit",
+    "aliases",
+    "permissions",
+    "shebang",
+    "set_flags",
+    "default_kinds",
+];
+
+impl Config {
+    /// `$TF_<KEY>` if it set `key`, for showing where a value came from.
+    pub fn env_source(&self, key: &str) -> Option<String> {
+        self.from_env
+            .contains(&key)
+            .then(|| format!("${}", env_var(key)))
+    }
+}
+
+fn env_var(key: &str) -> String {
+    format!("TF_{}", key.to_uppercase())
+}
+
+/// `$TF_CONFIG`, or `$XDG_CONFIG_HOME/tf/config.toml`.
+pub fn config_path() -> Option<PathBuf> {
+    env_config_path().or_else(|| dirs::config_dir().map(|dir| dir.join("tf").join("config.toml")))
+}
+
+fn env_config_path() -> Option<PathBuf> {
+    env::var_os("TF_CONFIG")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+fn required_config_path() -> Result<PathBuf, TfError> {
+    config_path()
+        .ok_or_else(|| TfError::Other(String::from("Couldn't determine the config directory.")))
+}
+
+/// Loads the config file, falling back to the defaults if there is none at
+/// the default path, with `$TF_<KEY>` environment variables overriding its
+/// values. A `$TF_CONFIG` that doesn't exist is an error.
+pub fn load() -> Result<Config, TfError> {
+    let path = config_path().filter(|path| path.is_file() || env_config_path().is_some());
+    let contents = match path {
+        Some(path) => {
+            let contents =
+                fs::read_to_string(&path).map_err(|e| TfError::from(e).context(path.display()))?;
+            toml::from_str::<Config>(&contents)
+                .map_err(|e| TfError::Usage(format!("{}: {e}", path.display())))?;
+            contents
+        }
+        None => String::new(),
+    };
+
+    let mut table: toml::Table =
+        toml::from_str(&contents).map_err(|e| TfError::Usage(e.to_string()))?;
+    let mut from_env = Vec::new();
+    for key in ENV_KEYS {
+        let var = env_var(key);
+        let Some(raw) = env::var(&var).ok().filter(|raw| !raw.trim().is_empty()) else {
+            continue;
+        };
+        let value = env_value(key, &raw).map_err(|e| TfError::Usage(format!("${var}: {e}")))?;
+        table.insert(key.to_string(), value);
+        from_env.push(*key);
+    }
+
+    let mut config = Config::deserialize(toml::Value::Table(table))
+        .map_err(|e| TfError::Usage(e.to_string()))?;
+    config.from_env = from_env;
+    Ok(config)
+}
+
+/// `raw` as the value of `key`: a string, unless the key expects another
+/// TOML type (e.g. `true` or `{ c = "lib" }`).
+fn env_value(key: &str, raw: &str) -> Result<toml::Value, String> {
+    let check = |value: &toml::Value| {
+        let table = toml::Table::from_iter([(key.to_string(), value.clone())]);
+        Config::deserialize(toml::Value::Table(table)).map_err(|e| e.message().to_string())
+    };
+
+    let as_string = toml::Value::String(raw.to_string());
+    let Err(e) = check(&as_string) else {
+        return Ok(as_string);
+    };
+    let parsed = toml::from_str::<toml::Table>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .ok_or(e)?;
+    check(&parsed)?;
+    Ok(parsed)
+}
+
+/// Writes the commented default config file, returning its path.
+pub fn init(force: bool) -> Result<PathBuf, TfError> {
+    let path = required_config_path()?;
+    if path.exists() && !force {
+        return Err(TfError::Exists(format!(
+            "'{}' already exists. Use '--force' to overwrite it.",
+            path.display()
+        )));
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, DEFAULT_CONFIG)?;
+    Ok(path)
+}
+
+/// Sets `key` to `value` in the config file, keeping its comments and
+/// formatting. The value is stored as a string unless the key expects
+/// another TOML type (e.g. `true` or `["a", "b"]`).
+pub fn set(key: &str, value: &str) -> Result<PathBuf, TfError> {
+    let path = required_config_path()?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::from(DEFAULT_CONFIG),
+        Err(e) => return Err(e.into()),
+    };
+    let mut document: DocumentMut = uncomment_key(&contents, key)
+        .parse()
+        .map_err(|e| TfError::Usage(format!("{}: {e}", path.display())))?;
+
+    document[key] = Item::Value(Value::from(value));
+    if let Err(as_string) = toml::from_str::<Config>(&document.to_string()) {
+        match value.parse::<Value>() {
+            Ok(parsed) => document[key] = Item::Value(parsed),
+            Err(_) => return Err(TfError::Usage(as_string.message().to_string())),
+        }
+        toml::from_str::<Config>(&document.to_string())
+            .map_err(|e| TfError::Usage(e.message().to_string()))?;
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, document.to_string())?;
+    Ok(path)
+}
+
+/// Turns the first `# key = ...` line into `key = ""` so a value set on a
+/// fresh config file lands next to its documentation.
+fn uncomment_key(contents: &str, key: &str) -> String {
+    let commented = format!("# {key} = ");
+    let Some(line) = contents.lines().find(|line| line.starts_with(&commented)) else {
+        return contents.to_string();
+    };
+
+    if contents
+        .parse::<DocumentMut>()
+        .is_ok_and(|document| document.contains_key(key))
+    {
+        return contents.to_string();
+    }
+
+    contents.replacen(line, &format!("{key} = \"\""), 1)
+}
@@ -0,0 +1,115 @@
+//! C++ settings: the standard templates target and the namespace sources
+//! and headers are wrapped in.
+
+use crate::config::Config;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// C++ standard, for templates that use newer idioms when they can.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, ValueEnum, Serialize, Deserialize)]
+pub enum Standard {
+    #[value(name = "c++11")]
+    #[serde(rename = "c++11")]
+    Cpp11,
+    #[value(name = "c++14")]
+    #[serde(rename = "c++14")]
+    Cpp14,
+    #[value(name = "c++17")]
+    #[serde(rename = "c++17")]
+    Cpp17,
+    #[value(name = "c++20")]
+    #[serde(rename = "c++20")]
+    Cpp20,
+    #[value(name = "c++23")]
+    #[serde(rename = "c++23")]
+    Cpp23,
+}
+
+impl Standard {
+    /// `{{ cpp_std }}`, e.g. `20` for C++20.
+    pub fn version(self) -> u16 {
+        match self {
+            Standard::Cpp11 => 11,
+            Standard::Cpp14 => 14,
+            Standard::Cpp17 => 17,
+            Standard::Cpp20 => 20,
+            Standard::Cpp23 => 23,
+        }
+    }
+}
+
+/// `{{ namespace }}`: the lines opening and closing a namespace, so
+/// templates don't have to spell out nesting before C++17.
+#[derive(Debug, Serialize)]
+pub struct Namespace {
+    /// `acme::uart`
+    pub name: String,
+    /// `namespace acme::uart {`, or `namespace acme { namespace uart {`
+    /// before C++17.
+    pub open: String,
+    /// `}  // namespace acme::uart`, with a brace per level before C++17.
+    pub close: String,
+}
+
+/// C++ settings after merging the command line with the config file.
+#[derive(Debug)]
+pub struct Options {
+    pub standard: Option<Standard>,
+    namespace: Vec<String>,
+}
+
+impl Options {
+    /// Accepts namespaces like `acme` or `acme::uart`.
+    pub fn resolve(
+        standard: Option<Standard>,
+        namespace: Option<&String>,
+        config: &Config,
+    ) -> Result<Options, String> {
+        let namespace: Vec<String> = match namespace.or(config.namespace.as_ref()) {
+            None => Vec::new(),
+            Some(namespace) => namespace
+                .trim()
+                .split("::")
+                .map(|part| {
+                    let legal = part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                        && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                    legal.then(|| part.to_string()).ok_or_else(|| {
+                        format!("'{namespace}' is not a valid namespace, e.g. 'acme::uart'.")
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        Ok(Options {
+            standard: standard.or(config.cpp_std),
+            namespace,
+        })
+    }
+
+    /// `None` if no namespace is set.
+    pub fn namespace(&self) -> Option<Namespace> {
+        if self.namespace.is_empty() {
+            return None;
+        }
+
+        let name = self.namespace.join("::");
+        let nested = self
+            .standard
+            .is_some_and(|standard| standard < Standard::Cpp17);
+        let (open, close) = if nested {
+            let open: Vec<String> = self
+                .namespace
+                .iter()
+                .map(|part| format!("namespace {part} {{"))
+                .collect();
+            let braces = "}".repeat(self.namespace.len());
+            (open.join(" "), format!("{braces}  // namespace {name}"))
+        } else {
+            (
+                format!("namespace {name} {{"),
+                format!("}}  // namespace {name}"),
+            )
+        };
+        Some(Namespace { name, open, close })
+    }
+}
@@ -0,0 +1,83 @@
+//! Dates and timestamps written into headers.
+
+use crate::config::Config;
+use chrono::{format::StrftimeItems, DateTime, Datelike, FixedOffset, Local, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// `{{ date }}` unless `--date-format`/`date_format` says otherwise.
+const US: &str = "%m/%d/%Y";
+const ISO: &str = "%Y-%m-%d";
+/// `{{ datetime }}`: ISO 8601 with the UTC offset, e.g.
+/// `2025-01-31T14:05:00+01:00`.
+const DATETIME: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// Timezone dates and timestamps are written in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Timezone {
+    /// The machine's local time
+    #[default]
+    Local,
+    /// Coordinated Universal Time
+    Utc,
+}
+
+/// Date settings after merging the command line with the config file.
+#[derive(Debug)]
+pub struct Options {
+    /// strftime format of `{{ date }}`.
+    pub format: String,
+    pub timezone: Timezone,
+}
+
+impl Options {
+    /// Accepts `us`, `iso` or a strftime format such as `%d.%m.%Y`.
+    pub fn resolve(format: Option<&String>, config: &Config) -> Result<Options, String> {
+        let format = match format.or(config.date_format.as_ref()).map(String::as_str) {
+            None | Some("us") => US,
+            Some("iso") => ISO,
+            Some(format) => format,
+        };
+        if StrftimeItems::new(format).parse().is_err() {
+            return Err(format!(
+                "'{format}' is not a valid date format. Use 'us', 'iso' or a strftime format like '%d.%m.%Y'."
+            ));
+        }
+
+        Ok(Options {
+            format: format.to_string(),
+            timezone: config.timezone.unwrap_or_default(),
+        })
+    }
+
+    /// Today's date, e.g. `01/31/2025`.
+    pub fn date(&self) -> String {
+        self.now().format(&self.format).to_string()
+    }
+
+    /// The current year, e.g. for copyright lines.
+    pub fn year(&self) -> i32 {
+        self.now().year()
+    }
+
+    /// The current time with its UTC offset.
+    pub fn datetime(&self) -> String {
+        self.now().format(DATETIME).to_string()
+    }
+
+    /// Now in the configured timezone. `$SOURCE_DATE_EPOCH` pins it, in UTC,
+    /// for reproducible output.
+    fn now(&self) -> DateTime<FixedOffset> {
+        let pinned = env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|epoch| epoch.trim().parse().ok())
+            .and_then(|epoch| DateTime::from_timestamp(epoch, 0));
+        match (pinned, self.timezone) {
+            (Some(pinned), _) => pinned.fixed_offset(),
+            (None, Timezone::Local) => Local::now().fixed_offset(),
+            (None, Timezone::Utc) => Utc::now().fixed_offset(),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Editors that take the line to jump to as `+<line> <file>`.
+const PLUS_LINE: &[&str] = &[
+    "vi",
+    "vim",
+    "nvim",
+    "gvim",
+    "nano",
+    "emacs",
+    "emacsclient",
+    "micro",
+    "kak",
+    "joe",
+    "ne",
+];
+
+/// Editors that take the line to jump to as `<file>:<line>`.
+const COLON_LINE: &[&str] = &["subl", "zed", "hx", "helix"];
+
+/// Editors that take the line to jump to as `--goto <file>:<line>`.
+const GOTO_LINE: &[&str] = &["code", "code-insiders", "codium"];
+
+/// Opens `paths` in `$VISUAL`/`$EDITOR`, with the cursor on the first file's
+/// `Purpose` line if the editor supports it. Waits for terminal editors.
+pub fn open(paths: &[PathBuf]) -> Result<(), String> {
+    let Some(first) = paths.first() else {
+        return Ok(());
+    };
+
+    let command = editor_command();
+    let mut words = command.split_whitespace();
+    let program = words
+        .next()
+        .ok_or_else(|| String::from("$VISUAL/$EDITOR is empty."))?;
+    let name = Path::new(program)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program);
+
+    let mut editor = Command::new(program);
+    editor.args(words);
+
+    let line = purpose_line(first);
+    if PLUS_LINE.contains(&name) {
+        editor.arg(format!("+{line}")).args(paths);
+    } else if COLON_LINE.contains(&name) || GOTO_LINE.contains(&name) {
+        if GOTO_LINE.contains(&name) {
+            editor.arg("--goto");
+        }
+        editor
+            .arg(format!("{}:{line}", first.display()))
+            .args(&paths[1..]);
+    } else {
+        editor.args(paths);
+    }
+
+    let status = editor
+        .status()
+        .map_err(|e| format!("Couldn't start editor '{program}': {e}"))?;
+    if !status.success() {
+        return Err(format!("Editor '{program}' exited with {status}"));
+    }
+    Ok(())
+}
+
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|key| env::var(key).ok().filter(|value| !value.trim().is_empty()))
+        .unwrap_or_else(|| String::from(if cfg!(windows) { "notepad" } else { "vi" }))
+}
+
+/// 1-based line of the header's `Purpose` entry, or the first line.
+fn purpose_line(path: &Path) -> usize {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| contents.lines().position(|line| line.contains("Purpose :")))
+        .map_or(1, |index| index + 1)
+}
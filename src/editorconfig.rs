@@ -0,0 +1,265 @@
+//! The `.editorconfig` properties tf honors when writing a file: indentation,
+//! the final newline, line endings and the byte order mark.
+
+use crate::encoding::LineEnding;
+use globset::GlobBuilder;
+use std::{
+    fs, io,
+    path::{self, Path},
+};
+
+/// Width of an indent level when neither the template nor `.editorconfig`
+/// shows one.
+pub const INDENT_WIDTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndentStyle {
+    Tab,
+    Space,
+}
+
+/// One level of indentation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Indent {
+    pub style: IndentStyle,
+    /// Spaces per level, or the width of a tab.
+    pub width: usize,
+}
+
+impl Indent {
+    /// The indentation `contents` was written with: tabs if any line starts
+    /// with one, otherwise the smallest run of leading spaces. Single spaces,
+    /// as in ` * ` comment lines, aren't indentation.
+    pub fn detect(contents: &str) -> Option<Indent> {
+        if contents.lines().any(|line| line.starts_with('\t')) {
+            return Some(Indent {
+                style: IndentStyle::Tab,
+                width: INDENT_WIDTH,
+            });
+        }
+        contents
+            .lines()
+            .map(|line| line.len() - line.trim_start_matches(' ').len())
+            .filter(|spaces| *spaces >= 2)
+            .min()
+            .map(|width| Indent {
+                style: IndentStyle::Space,
+                width,
+            })
+    }
+}
+
+/// The properties of one file, from the `.editorconfig` files above it.
+#[derive(Debug, Default)]
+pub struct Properties {
+    pub indent_style: Option<IndentStyle>,
+    /// `None` for `indent_size = tab`, which defers to `tab_width`.
+    pub indent_size: Option<usize>,
+    pub tab_width: Option<usize>,
+    pub insert_final_newline: Option<bool>,
+    /// `end_of_line`, unless it's the unsupported `cr`.
+    pub end_of_line: Option<LineEnding>,
+    /// Whether `charset` is `utf-8-bom` rather than `utf-8`. Other charsets
+    /// aren't supported and leave it unset.
+    pub bom: Option<bool>,
+}
+
+impl Properties {
+    /// The properties of `path`, which doesn't need to exist yet. Files
+    /// closer to it win, and a file with `root = true` ends the search.
+    pub fn of(path: &Path) -> Result<Properties, String> {
+        let path = path::absolute(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut files = Vec::new();
+        for dir in path.ancestors().skip(1) {
+            let file = dir.join(".editorconfig");
+            match fs::read_to_string(&file) {
+                Ok(contents) => {
+                    let root = is_root(&contents);
+                    files.push((dir, contents));
+                    if root {
+                        break;
+                    }
+                }
+                // Also a directory that's yet to be created where a file is.
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::NotFound | io::ErrorKind::NotADirectory
+                    ) => {}
+                Err(e) => return Err(format!("reading '{}': {e}", file.display())),
+            }
+        }
+
+        let mut properties = Properties::default();
+        for (dir, contents) in files.iter().rev() {
+            let Ok(relative) = path.strip_prefix(dir) else {
+                continue;
+            };
+            properties.read(&slash_path(relative), contents);
+        }
+        Ok(properties)
+    }
+
+    /// The indentation to write: `fixed` if the syntax demands a style, then
+    /// `.editorconfig`, then what the template used.
+    pub fn indent(&self, fixed: Option<IndentStyle>, template: Option<Indent>) -> Indent {
+        let style = fixed
+            .or(self.indent_style)
+            .or(template.map(|indent| indent.style))
+            .unwrap_or(IndentStyle::Space);
+        let width = match style {
+            IndentStyle::Tab => self.tab_width.or(self.indent_size),
+            IndentStyle::Space => self.indent_size.or(self.tab_width),
+        };
+        Indent {
+            style,
+            width: width
+                .or(template.map(|indent| indent.width))
+                .unwrap_or(INDENT_WIDTH),
+        }
+    }
+
+    /// Applies the sections of one `.editorconfig` matching `relative`, the
+    /// path of the file below the `.editorconfig`'s directory.
+    fn read(&mut self, relative: &str, contents: &str) {
+        let mut matches = false;
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+            if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                matches = section_matches(section, relative);
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            if !matches {
+                continue;
+            }
+
+            let value = value.trim().to_lowercase();
+            let unset = value == "unset";
+            match key.trim().to_lowercase().as_str() {
+                "indent_style" => {
+                    self.indent_style = match value.as_str() {
+                        "tab" => Some(IndentStyle::Tab),
+                        "space" => Some(IndentStyle::Space),
+                        _ if unset => None,
+                        _ => self.indent_style,
+                    }
+                }
+                "indent_size" if value == "tab" || unset => self.indent_size = None,
+                "indent_size" => self.indent_size = width(&value).or(self.indent_size),
+                "tab_width" if unset => self.tab_width = None,
+                "tab_width" => self.tab_width = width(&value).or(self.tab_width),
+                "insert_final_newline" => {
+                    self.insert_final_newline = match value.as_str() {
+                        "true" => Some(true),
+                        "false" => Some(false),
+                        _ if unset => None,
+                        _ => self.insert_final_newline,
+                    }
+                }
+                "end_of_line" => {
+                    self.end_of_line = match value.as_str() {
+                        "lf" => Some(LineEnding::Lf),
+                        "crlf" => Some(LineEnding::Crlf),
+                        _ if unset => None,
+                        _ => self.end_of_line,
+                    }
+                }
+                "charset" => {
+                    self.bom = match value.as_str() {
+                        "utf-8" => Some(false),
+                        "utf-8-bom" => Some(true),
+                        _ if unset => None,
+                        _ => self.bom,
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Whether the preamble (the lines before the first section) says
+/// `root = true`.
+fn is_root(contents: &str) -> bool {
+    contents
+        .lines()
+        .map(str::trim)
+        .take_while(|line| !line.starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .any(|(key, value)| {
+            key.trim().eq_ignore_ascii_case("root") && value.trim().eq_ignore_ascii_case("true")
+        })
+}
+
+/// `relative` with `/` separators, as section globs are written.
+fn slash_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether the section `[glob]` applies to `relative`. Globs without a `/`
+/// match the filename in any directory, the others match from the
+/// `.editorconfig`'s directory. Globs that don't parse match nothing.
+fn section_matches(glob: &str, relative: &str) -> bool {
+    let glob = match glob.strip_prefix('/') {
+        Some(anchored) => anchored.to_string(),
+        None if glob.contains('/') => glob.to_string(),
+        None => format!("**/{glob}"),
+    };
+    GlobBuilder::new(&glob)
+        .literal_separator(true)
+        .build()
+        .is_ok_and(|glob| glob.compile_matcher().is_match(relative))
+}
+
+fn width(value: &str) -> Option<usize> {
+    value.parse().ok().filter(|width| *width > 0)
+}
+
+/// `contents` re-indented from the `from` indentation to `to`. Leading
+/// whitespace that isn't a whole number of levels keeps the remainder as
+/// spaces, so alignment inside comments survives.
+pub fn reindent(contents: &str, from: Indent, to: Indent) -> String {
+    if from == to {
+        return contents.to_string();
+    }
+
+    contents
+        .split_inclusive('\n')
+        .map(|line| {
+            let body = line.trim_start_matches([' ', '\t']);
+            let leading = &line[..line.len() - body.len()];
+            let (levels, rest) = match from.style {
+                IndentStyle::Tab => {
+                    let tabs = leading.len() - leading.trim_start_matches('\t').len();
+                    (tabs, leading.len() - tabs)
+                }
+                IndentStyle::Space if leading.contains('\t') => return line.to_string(),
+                IndentStyle::Space => (leading.len() / from.width, leading.len() % from.width),
+            };
+            let indent = match to.style {
+                IndentStyle::Tab => "\t".repeat(levels),
+                IndentStyle::Space => " ".repeat(levels * to.width),
+            };
+            format!("{indent}{}{body}", " ".repeat(rest))
+        })
+        .collect()
+}
+
+/// `contents` with exactly one final newline, or none.
+pub fn final_newline(contents: &str, insert: bool) -> String {
+    let trimmed = contents.trim_end_matches(['\r', '\n']);
+    if insert && !trimmed.is_empty() {
+        format!("{trimmed}\n")
+    } else {
+        trimmed.to_string()
+    }
+}
@@ -0,0 +1,101 @@
+//! Line endings and the UTF-8 byte order mark of generated files.
+
+use crate::{config::Config, editorconfig::Properties, filetype::FileType};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LineEnding {
+    /// `\n`, as on Linux and macOS
+    #[default]
+    Lf,
+    /// `\r\n`, as on Windows
+    Crlf,
+}
+
+/// The UTF-8 byte order mark.
+pub const BOM: char = '\u{feff}';
+
+/// Encoding settings after merging the command line with the config file.
+#[derive(Debug)]
+pub struct Options {
+    line_ending: Option<LineEnding>,
+    /// The config's line endings by filetype.
+    line_endings: BTreeMap<String, LineEnding>,
+    default_line_ending: Option<LineEnding>,
+    bom: Option<bool>,
+    default_bom: Option<bool>,
+}
+
+impl Options {
+    pub fn resolve(line_ending: Option<LineEnding>, bom: Option<bool>, config: &Config) -> Options {
+        Options {
+            line_ending,
+            line_endings: config.filetype_line_endings.clone(),
+            default_line_ending: config.line_endings,
+            bom,
+            default_bom: config.bom,
+        }
+    }
+
+    /// The line ending of `filetype`: the flag, then `.editorconfig`, then
+    /// the config by filetype and in general, then LF.
+    pub fn line_ending(&self, filetype: &FileType, properties: &Properties) -> LineEnding {
+        self.line_ending
+            .or(properties.end_of_line)
+            .or_else(|| {
+                filetype
+                    .config_keys()
+                    .find_map(|key| self.line_endings.get(key).copied())
+            })
+            .or(self.default_line_ending)
+            .unwrap_or_default()
+    }
+
+    /// Whether to start the file with a byte order mark: the flag, then
+    /// `.editorconfig`'s `charset`, then the config. Off unless set.
+    pub fn bom(&self, properties: &Properties) -> bool {
+        self.bom
+            .or(properties.bom)
+            .or(self.default_bom)
+            .unwrap_or(false)
+    }
+}
+
+/// `contents` with LF line endings, whatever the template was saved with.
+pub fn normalize(contents: &str) -> String {
+    contents.replace("\r\n", "\n")
+}
+
+/// The byte order mark `contents` starts with, if any, and the rest.
+pub fn split_bom(contents: &str) -> (&str, &str) {
+    let len = if contents.starts_with(BOM) {
+        BOM.len_utf8()
+    } else {
+        0
+    };
+    contents.split_at(len)
+}
+
+/// The line ending of the first line of `contents`, LF if it has none.
+pub fn detect(contents: &str) -> LineEnding {
+    match contents.split_inclusive('\n').next() {
+        Some(line) if line.ends_with("\r\n") => LineEnding::Crlf,
+        _ => LineEnding::Lf,
+    }
+}
+
+/// `contents`, which has LF line endings, as written to disk.
+pub fn encode(contents: &str, line_ending: LineEnding, bom: bool) -> String {
+    let contents = match line_ending {
+        LineEnding::Lf => contents.to_string(),
+        LineEnding::Crlf => contents.replace('\n', "\r\n"),
+    };
+    if bom {
+        format!("{BOM}{contents}")
+    } else {
+        contents
+    }
+}
@@ -0,0 +1,62 @@
+//! Why a command failed, and the exit code that tells scripts.
+
+use std::{fmt::Display, io};
+use thiserror::Error;
+
+/// A failure of any tf command. The message is shown as is; the variant picks the
+/// exit code, so wrappers can tell "file exists" from "bad extension".
+#[derive(Debug, Error)]
+pub enum TfError {
+    /// Anything else, e.g. a template that doesn't render.
+    #[error("{0}")]
+    Other(String),
+    /// The command line doesn't make sense, e.g. a kind that doesn't apply.
+    #[error("{0}")]
+    Usage(String),
+    /// A file to create already exists.
+    #[error("{0}")]
+    Exists(String),
+    /// The filetype can't be detected or isn't supported.
+    #[error("{0}")]
+    Unsupported(String),
+    /// Reading or writing a file failed.
+    #[error("{0}")]
+    Io(String),
+    /// Failures already shown one by one (or in the JSON report), with the
+    /// exit code of the first.
+    #[error("see the errors above")]
+    Reported(i32),
+}
+
+impl TfError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TfError::Other(_) => 1,
+            TfError::Usage(_) => 2,
+            TfError::Exists(_) => 3,
+            TfError::Unsupported(_) => 4,
+            TfError::Io(_) => 5,
+            TfError::Reported(code) => *code,
+        }
+    }
+
+    /// The same failure with `context` (e.g. the file it's about) in front
+    /// of its message.
+    pub fn context(self, context: impl Display) -> TfError {
+        let prefixed = |message: String| format!("{context}: {message}");
+        match self {
+            TfError::Other(message) => TfError::Other(prefixed(message)),
+            TfError::Usage(message) => TfError::Usage(prefixed(message)),
+            TfError::Exists(message) => TfError::Exists(prefixed(message)),
+            TfError::Unsupported(message) => TfError::Unsupported(prefixed(message)),
+            TfError::Io(message) => TfError::Io(prefixed(message)),
+            TfError::Reported(code) => TfError::Reported(code),
+        }
+    }
+}
+
+impl From<io::Error> for TfError {
+    fn from(e: io::Error) -> TfError {
+        TfError::Io(e.to_string())
+    }
+}
@@ -0,0 +1,89 @@
+//! Simulator filelists (`.f`) kept in sync with generated HDL files.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{self, Component, Path, PathBuf},
+};
+
+/// Extensions of the files `--filelist` adds.
+const EXTENSIONS: &[&str] = &["sv", "svh", "v"];
+
+/// Entries for the HDL files among `paths` that `filelist` doesn't list yet,
+/// relative to the filelist's directory so it works with `-F`.
+pub fn missing_entries(filelist: &Path, paths: &[PathBuf]) -> Vec<String> {
+    let listed = fs::read_to_string(filelist).unwrap_or_default();
+    let dir = filelist.parent().unwrap_or(Path::new(""));
+
+    let mut entries: Vec<String> = Vec::new();
+    for path in paths {
+        let hdl = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| EXTENSIONS.contains(&ext));
+        if !hdl {
+            continue;
+        }
+        let entry = relative(path, dir);
+        if !listed.lines().any(|line| line.trim() == entry) && !entries.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    entries
+}
+
+/// Appends `entries` to `filelist`, which must exist, with the line endings
+/// it already has.
+pub fn append(filelist: &Path, entries: &[String]) -> io::Result<()> {
+    let contents = fs::read_to_string(filelist)?;
+    let eol = if contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut file = OpenOptions::new().append(true).open(filelist)?;
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        write!(file, "{eol}")?;
+    }
+    for entry in entries {
+        write!(file, "{entry}{eol}")?;
+    }
+    Ok(())
+}
+
+/// `path` relative to `dir`, with `/` separators, e.g. `../rtl/uart.sv`.
+fn relative(path: &Path, dir: &Path) -> String {
+    let (Ok(path), Ok(dir)) = (absolute(path), absolute(dir)) else {
+        return path.to_string_lossy().replace('\\', "/");
+    };
+
+    let common = path
+        .components()
+        .zip(dir.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return path.to_string_lossy().replace('\\', "/");
+    }
+
+    let mut words: Vec<String> = vec![String::from(".."); dir.components().count() - common];
+    words.extend(
+        path.components()
+            .skip(common)
+            .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+    );
+    words.join("/")
+}
+
+/// `path` made absolute, with `.` components dropped.
+fn absolute(path: &Path) -> io::Result<PathBuf> {
+    let path = if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path
+    };
+    Ok(path::absolute(path)?
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect())
+}
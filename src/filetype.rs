@@ -0,0 +1,931 @@
+use crate::{config::value_name, editorconfig::IndentStyle, template::CommentStyle};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Template variants for extensions that more than one template shares.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    /// Python script with `argparse` (`.py`)
+    Script,
+    /// pytest test file (`.py`), the default for `test_*.py`/`*_test.py`
+    Test,
+    /// Class skeleton (`.cpp` with a matching `.hpp`, `.hpp`, `.py`)
+    Class,
+    /// Program entry point with `main()` (`.c`, `.cpp`), the default unless
+    /// `default_kinds` in the config says otherwise
+    Main,
+    /// Library source without `main()` (`.c`, `.cpp`)
+    Lib,
+    /// SystemVerilog testbench (`.sv`)
+    Tb,
+    /// SystemVerilog interface (`.sv`)
+    Interface,
+    /// UVM driver (`.sv`/`.svh`)
+    UvmDriver,
+    /// UVM monitor (`.sv`/`.svh`)
+    UvmMonitor,
+    /// UVM agent (`.sv`/`.svh`)
+    UvmAgent,
+    /// UVM sequence (`.sv`/`.svh`)
+    UvmSequence,
+    /// UVM environment (`.sv`/`.svh`)
+    UvmEnv,
+    /// UVM test (`.sv`/`.svh`)
+    UvmTest,
+}
+
+/// Heading a filetype is listed under by `tf list`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Category {
+    Software,
+    Build,
+    Config,
+    Docs,
+    Hdl,
+    Uvm,
+    User,
+}
+
+impl Category {
+    pub const ALL: [Category; 7] = [
+        Category::Software,
+        Category::Build,
+        Category::Config,
+        Category::Docs,
+        Category::Hdl,
+        Category::Uvm,
+        Category::User,
+    ];
+
+    pub fn heading(&self) -> &'static str {
+        match self {
+            Category::Software => "Software Filetypes:",
+            Category::Build => "Build Filetypes:",
+            Category::Config => "Config Filetypes:",
+            Category::Docs => "Document Filetypes:",
+            Category::Hdl => "HDL Filetypes:",
+            Category::Uvm => "UVM Filetypes:",
+            Category::User => "User Filetypes:",
+        }
+    }
+}
+
+/// Everything tf needs to know to generate one kind of file.
+#[derive(Debug, Clone)]
+pub struct FileType {
+    /// Template name. A user template called `<name>.tera` overrides it.
+    pub name: Cow<'static, str>,
+    /// Human readable name of the filetype.
+    pub description: &'static str,
+    pub category: Category,
+    /// Extensions (without the dot) detected as this filetype.
+    pub extensions: &'static [&'static str],
+    /// Whole filenames detected as this filetype, e.g. `Makefile`.
+    pub file_names: &'static [&'static str],
+    /// Directory `extensions` only apply in, e.g. `.github/workflows`.
+    pub dir: Option<&'static str>,
+    /// The `--kind` that selects this filetype instead of one of `bases`.
+    pub kind: Option<Kind>,
+    /// The `--kind` that selects this filetype over its own variants, e.g.
+    /// `main` for C.
+    pub base_kind: Option<Kind>,
+    /// Names of the filetypes `kind` applies to.
+    pub bases: &'static [&'static str],
+    /// Extension and name of a filetype generated alongside this one, e.g.
+    /// the class declaration for a C++ class implementation.
+    pub companion: Option<(&'static str, &'static str)>,
+    /// How the header banner is commented out.
+    pub comment: CommentStyle,
+    /// Permissions set on the generated file, if not the default.
+    pub mode: Option<u32>,
+    /// Whether the stem names something in the generated code (a module,
+    /// class or package), so it must be a legal identifier.
+    pub stem_is_identifier: bool,
+    /// Whether the class is named exactly like the file, as Java requires,
+    /// rather than after the stem in PascalCase.
+    pub class_is_stem: bool,
+    /// Indent style the syntax requires (tabs in Makefile recipes, spaces
+    /// in YAML), which `.editorconfig` can't change.
+    pub indent_style: Option<IndentStyle>,
+    /// Built-in template, empty for filetypes defined by user templates.
+    pub source: &'static str,
+}
+
+const fn builtin(
+    name: &'static str,
+    description: &'static str,
+    category: Category,
+    extensions: &'static [&'static str],
+    comment: CommentStyle,
+    source: &'static str,
+) -> FileType {
+    FileType {
+        name: Cow::Borrowed(name),
+        description,
+        category,
+        extensions,
+        file_names: &[],
+        dir: None,
+        kind: None,
+        base_kind: None,
+        bases: &[],
+        companion: None,
+        comment,
+        mode: None,
+        stem_is_identifier: false,
+        class_is_stem: false,
+        indent_style: None,
+        source,
+    }
+}
+
+const fn variant(
+    name: &'static str,
+    description: &'static str,
+    category: Category,
+    kind: Kind,
+    bases: &'static [&'static str],
+    comment: CommentStyle,
+    source: &'static str,
+) -> FileType {
+    let mut filetype = builtin(name, description, category, &[], comment, source);
+    filetype.kind = Some(kind);
+    filetype.bases = bases;
+    filetype
+}
+
+/// Every built-in filetype. Adding a template only takes a new entry here.
+pub const BUILTINS: &[FileType] = &[
+    builtin(
+        "c",
+        "C",
+        Category::Software,
+        &["c"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/c.tera"),
+    )
+    .with_base_kind(Kind::Main),
+    variant(
+        "c_lib",
+        "C (library)",
+        Category::Software,
+        Kind::Lib,
+        &["c"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/c_lib.tera"),
+    )
+    .stem_is_identifier(),
+    builtin(
+        "h",
+        "H",
+        Category::Software,
+        &["h"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/h.tera"),
+    ),
+    builtin(
+        "py",
+        "Python",
+        Category::Software,
+        &["py"],
+        CommentStyle::DOCSTRING,
+        include_str!("../templates/py.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "py_class",
+        "Python (class)",
+        Category::Software,
+        Kind::Class,
+        &["py"],
+        CommentStyle::DOCSTRING,
+        include_str!("../templates/py_class.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "py_script",
+        "Python (script)",
+        Category::Software,
+        Kind::Script,
+        &["py"],
+        CommentStyle::DOCSTRING,
+        include_str!("../templates/py_script.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "py_test",
+        "Python (pytest)",
+        Category::Software,
+        Kind::Test,
+        &["py"],
+        CommentStyle::DOCSTRING,
+        include_str!("../templates/py_test.tera"),
+    )
+    .stem_is_identifier(),
+    builtin(
+        "cpp",
+        "CPP",
+        Category::Software,
+        &["cpp"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/cpp.tera"),
+    )
+    .with_base_kind(Kind::Main),
+    variant(
+        "cpp_lib",
+        "CPP (library)",
+        Category::Software,
+        Kind::Lib,
+        &["cpp"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/cpp_lib.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "cpp_class",
+        "CPP (class)",
+        Category::Software,
+        Kind::Class,
+        &["cpp"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/cpp_class.tera"),
+    )
+    .with_companion("hpp", "hpp_class")
+    .stem_is_identifier(),
+    builtin(
+        "hpp",
+        "HPP",
+        Category::Software,
+        &["hpp"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/hpp.tera"),
+    ),
+    variant(
+        "hpp_class",
+        "HPP (class)",
+        Category::Software,
+        Kind::Class,
+        &["hpp"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/hpp_class.tera"),
+    )
+    .stem_is_identifier(),
+    builtin(
+        "bash",
+        "Bash",
+        Category::Software,
+        &["bash"],
+        CommentStyle::HASHES,
+        include_str!("../templates/bash.tera"),
+    )
+    .with_mode(0o744),
+    builtin(
+        "bat",
+        "Batch",
+        Category::Software,
+        &["bat", "cmd"],
+        CommentStyle::REM,
+        include_str!("../templates/bat.tera"),
+    ),
+    builtin(
+        "ps1",
+        "PowerShell",
+        Category::Software,
+        &["ps1"],
+        CommentStyle::HASHES,
+        include_str!("../templates/ps1.tera"),
+    ),
+    builtin(
+        "go",
+        "Go",
+        Category::Software,
+        &["go"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/go.tera"),
+    ),
+    builtin(
+        "java",
+        "Java",
+        Category::Software,
+        &["java"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/java.tera"),
+    )
+    .stem_is_identifier()
+    .class_is_stem(),
+    builtin(
+        "ts",
+        "TypeScript",
+        Category::Software,
+        &["ts"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/ts.tera"),
+    ),
+    builtin(
+        "js",
+        "JavaScript",
+        Category::Software,
+        &["js"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/js.tera"),
+    ),
+    builtin(
+        "zig",
+        "Zig",
+        Category::Software,
+        &["zig"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/zig.tera"),
+    ),
+    builtin(
+        "lua",
+        "Lua",
+        Category::Software,
+        &["lua"],
+        CommentStyle::DASHES,
+        include_str!("../templates/lua.tera"),
+    ),
+    builtin(
+        "rb",
+        "Ruby",
+        Category::Software,
+        &["rb"],
+        CommentStyle::HASHES,
+        include_str!("../templates/rb.tera"),
+    ),
+    builtin(
+        "s",
+        "Assembly (RISC-V/ARM)",
+        Category::Software,
+        &["s", "S"],
+        CommentStyle::BLOCK,
+        include_str!("../templates/s.tera"),
+    ),
+    builtin(
+        "proto",
+        "Protocol Buffers",
+        Category::Software,
+        &["proto"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/proto.tera"),
+    )
+    .stem_is_identifier(),
+    builtin(
+        "makefile",
+        "Makefile",
+        Category::Build,
+        &["mk"],
+        CommentStyle::HASHES,
+        include_str!("../templates/makefile.tera"),
+    )
+    .with_file_names(&["Makefile", "makefile", "GNUmakefile"])
+    .with_indent_style(IndentStyle::Tab),
+    builtin(
+        "ld",
+        "Linker script",
+        Category::Build,
+        &["ld"],
+        CommentStyle::BLOCK,
+        include_str!("../templates/ld.tera"),
+    ),
+    builtin(
+        "cmake",
+        "CMake",
+        Category::Build,
+        &[],
+        CommentStyle::HASHES,
+        include_str!("../templates/cmake.tera"),
+    )
+    .with_file_names(&["CMakeLists.txt"]),
+    builtin(
+        "dockerfile",
+        "Dockerfile",
+        Category::Build,
+        &["dockerfile"],
+        CommentStyle::HASHES,
+        include_str!("../templates/dockerfile.tera"),
+    )
+    .with_file_names(&["Dockerfile", "Containerfile"]),
+    builtin(
+        "compose",
+        "Docker Compose",
+        Category::Build,
+        &[],
+        CommentStyle::HASHES,
+        include_str!("../templates/compose.tera"),
+    )
+    .with_file_names(&["docker-compose.yml", "compose.yaml", "compose.yml"])
+    .with_indent_style(IndentStyle::Space),
+    builtin(
+        "workflow",
+        "GitHub Actions workflow",
+        Category::Build,
+        &["yml", "yaml"],
+        CommentStyle::HASHES,
+        include_str!("../templates/workflow.tera"),
+    )
+    .in_dir(".github/workflows")
+    .with_indent_style(IndentStyle::Space),
+    builtin(
+        "toml",
+        "TOML",
+        Category::Config,
+        &["toml"],
+        CommentStyle::HASHES,
+        include_str!("../templates/toml.tera"),
+    ),
+    builtin(
+        "yaml",
+        "YAML",
+        Category::Config,
+        &["yaml", "yml"],
+        CommentStyle::HASHES,
+        include_str!("../templates/yaml.tera"),
+    )
+    .with_indent_style(IndentStyle::Space),
+    builtin(
+        "json",
+        "JSON",
+        Category::Config,
+        &["json"],
+        CommentStyle::NONE,
+        include_str!("../templates/json.tera"),
+    ),
+    builtin(
+        "md",
+        "Markdown",
+        Category::Docs,
+        &["md", "markdown"],
+        CommentStyle::HTML,
+        include_str!("../templates/md.tera"),
+    ),
+    builtin(
+        "tex",
+        "LaTeX",
+        Category::Docs,
+        &["tex"],
+        CommentStyle::PERCENT,
+        include_str!("../templates/tex.tera"),
+    ),
+    builtin(
+        "v",
+        "Verilog",
+        Category::Hdl,
+        &["v"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/v.tera"),
+    )
+    .stem_is_identifier(),
+    builtin(
+        "vhd",
+        "VHDL",
+        Category::Hdl,
+        &["vhd", "vhdl"],
+        CommentStyle::DASHES,
+        include_str!("../templates/vhd.tera"),
+    )
+    .stem_is_identifier(),
+    builtin(
+        "sv",
+        "SystemVerilog (module)",
+        Category::Hdl,
+        &["sv"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv.tera"),
+    )
+    .stem_is_identifier(),
+    builtin(
+        "svh",
+        "SystemVerilog (package)",
+        Category::Hdl,
+        &["svh"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/svh.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "sv_tb",
+        "SystemVerilog (testbench)",
+        Category::Hdl,
+        Kind::Tb,
+        &["sv"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv_tb.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "sv_interface",
+        "SystemVerilog (interface)",
+        Category::Hdl,
+        Kind::Interface,
+        &["sv"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv_interface.tera"),
+    )
+    .stem_is_identifier(),
+    builtin(
+        "f",
+        "Filelist",
+        Category::Hdl,
+        &["f"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/f.tera"),
+    ),
+    builtin(
+        "tcl",
+        "Tcl (Vivado/Quartus)",
+        Category::Hdl,
+        &["tcl"],
+        CommentStyle::HASHES,
+        include_str!("../templates/tcl.tera"),
+    ),
+    builtin(
+        "xdc",
+        "Constraints (XDC)",
+        Category::Hdl,
+        &["xdc"],
+        CommentStyle::HASHES,
+        include_str!("../templates/xdc.tera"),
+    ),
+    builtin(
+        "sdc",
+        "Constraints (SDC)",
+        Category::Hdl,
+        &["sdc"],
+        CommentStyle::HASHES,
+        include_str!("../templates/sdc.tera"),
+    ),
+    variant(
+        "sv_uvm_driver",
+        "UVM (driver)",
+        Category::Uvm,
+        Kind::UvmDriver,
+        &["sv", "svh"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv_uvm_driver.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "sv_uvm_monitor",
+        "UVM (monitor)",
+        Category::Uvm,
+        Kind::UvmMonitor,
+        &["sv", "svh"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv_uvm_monitor.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "sv_uvm_agent",
+        "UVM (agent)",
+        Category::Uvm,
+        Kind::UvmAgent,
+        &["sv", "svh"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv_uvm_agent.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "sv_uvm_sequence",
+        "UVM (sequence)",
+        Category::Uvm,
+        Kind::UvmSequence,
+        &["sv", "svh"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv_uvm_sequence.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "sv_uvm_env",
+        "UVM (env)",
+        Category::Uvm,
+        Kind::UvmEnv,
+        &["sv", "svh"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv_uvm_env.tera"),
+    )
+    .stem_is_identifier(),
+    variant(
+        "sv_uvm_test",
+        "UVM (test)",
+        Category::Uvm,
+        Kind::UvmTest,
+        &["sv", "svh"],
+        CommentStyle::SLASHES,
+        include_str!("../templates/sv_uvm_test.tera"),
+    )
+    .stem_is_identifier(),
+];
+
+impl FileType {
+    const fn with_file_names(mut self, file_names: &'static [&'static str]) -> FileType {
+        self.file_names = file_names;
+        self
+    }
+
+    const fn with_base_kind(mut self, kind: Kind) -> FileType {
+        self.base_kind = Some(kind);
+        self
+    }
+
+    const fn in_dir(mut self, dir: &'static str) -> FileType {
+        self.dir = Some(dir);
+        self
+    }
+
+    const fn with_companion(mut self, extension: &'static str, name: &'static str) -> FileType {
+        self.companion = Some((extension, name));
+        self
+    }
+
+    const fn stem_is_identifier(mut self) -> FileType {
+        self.stem_is_identifier = true;
+        self
+    }
+
+    const fn class_is_stem(mut self) -> FileType {
+        self.class_is_stem = true;
+        self
+    }
+
+    const fn with_mode(mut self, mode: u32) -> FileType {
+        self.mode = Some(mode);
+        self
+    }
+
+    const fn with_indent_style(mut self, style: IndentStyle) -> FileType {
+        self.indent_style = Some(style);
+        self
+    }
+
+    /// A filetype defined by a user template `<name>.tera` whose name isn't a
+    /// built-in. Files with the extension `<name>` are rendered with it.
+    fn user(name: String) -> FileType {
+        let mut filetype = builtin(
+            "",
+            "User template",
+            Category::User,
+            &[],
+            CommentStyle::HASHES,
+            "",
+        );
+        filetype.name = Cow::Owned(name);
+        filetype
+    }
+
+    /// Its name followed by its bases', the keys per-filetype config
+    /// settings are looked up by (so `py` also covers `py_script`).
+    pub fn config_keys(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_ref()).chain(self.bases.iter().copied())
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        self.category != Category::User
+    }
+
+    /// How files of this type are selected on the command line, e.g.
+    /// `Makefile/.mk` or `.sv/.svh --kind uvm-driver`.
+    pub fn usage(&self, registry: &Registry) -> String {
+        match self.kind {
+            Some(kind) => {
+                let bases: Vec<String> = self
+                    .bases
+                    .iter()
+                    .filter_map(|base| registry.find(base))
+                    .flat_map(|base| registry.names_of(base))
+                    .collect();
+                let mut usage = format!("{} --kind {}", bases.join("/"), value_name(&kind));
+                for alias in registry.aliases_of(self) {
+                    usage.push_str(&format!(", .{alias}"));
+                }
+                usage
+            }
+            None => {
+                let names = registry.names_of(self);
+                if names.is_empty() {
+                    String::from("-")
+                } else {
+                    names.join("/")
+                }
+            }
+        }
+    }
+}
+
+/// The built-in filetypes merged with the ones defined by user templates.
+pub struct Registry {
+    pub filetypes: Vec<FileType>,
+    template_dirs: Vec<PathBuf>,
+    /// Extra extensions and the name of the filetype they map to.
+    aliases: Vec<(String, String)>,
+}
+
+impl Registry {
+    /// Loads the registry for `template_dirs`, highest precedence first,
+    /// with `aliases` mapping extra extensions to existing filetypes by name
+    /// or extension (e.g. `cc` to `cpp`).
+    pub fn load(
+        template_dirs: &[PathBuf],
+        aliases: &BTreeMap<String, String>,
+    ) -> Result<Registry, String> {
+        let mut filetypes = BUILTINS.to_vec();
+
+        let mut user_names: Vec<String> = template_dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                (path.extension()? == "tera")
+                    .then(|| path.file_stem()?.to_str().map(String::from))
+                    .flatten()
+            })
+            .filter(|name| {
+                !crate::template::is_partial(name) && !BUILTINS.iter().any(|ft| ft.name == *name)
+            })
+            .collect();
+        user_names.sort();
+        user_names.dedup();
+        filetypes.extend(user_names.into_iter().map(FileType::user));
+
+        let mut registry = Registry {
+            filetypes,
+            template_dirs: template_dirs.to_vec(),
+            aliases: Vec::new(),
+        };
+
+        for (extension, target) in aliases {
+            let extension = extension.trim_start_matches('.');
+            let filetype = registry
+                .find(target.trim_start_matches('.'))
+                .or_else(|| registry.by_extension(target.trim_start_matches('.')))
+                .ok_or_else(|| {
+                    format!(
+                        "Alias '.{extension}' points to unknown filetype '{target}'. Run 'tf list' for available filetypes."
+                    )
+                })?;
+            let name = filetype.name.to_string();
+            registry.aliases.push((extension.to_string(), name));
+        }
+
+        Ok(registry)
+    }
+
+    /// Aliased extensions of `filetype`.
+    pub fn aliases_of<'a>(&'a self, filetype: &'a FileType) -> impl Iterator<Item = &'a str> {
+        self.aliases
+            .iter()
+            .filter(move |(_, name)| *name == filetype.name)
+            .map(|(extension, _)| extension.as_str())
+    }
+
+    /// Filenames (`Makefile`), extensions (`.mk`) and paths
+    /// (`.github/workflows/*.yml`) detected as `filetype`, leaving out
+    /// extensions an alias maps elsewhere.
+    pub fn names_of(&self, filetype: &FileType) -> Vec<String> {
+        let mut names: Vec<String> = filetype.file_names.iter().map(|n| n.to_string()).collect();
+        if let Some(dir) = filetype.dir {
+            names.extend(filetype.extensions.iter().enumerate().map(|(i, ext)| {
+                if i == 0 {
+                    format!("{dir}/*.{ext}")
+                } else {
+                    format!("*.{ext}")
+                }
+            }));
+            return names;
+        }
+        names.extend(
+            filetype
+                .extensions
+                .iter()
+                .filter(|ext| {
+                    self.by_extension(ext)
+                        .is_some_and(|detected| detected.name == filetype.name)
+                })
+                .map(|ext| format!(".{ext}")),
+        );
+        if !filetype.is_builtin() {
+            names.push(format!(".{}", filetype.name));
+        }
+        names.extend(self.aliases_of(filetype).map(|ext| format!(".{ext}")));
+        names
+    }
+
+    /// Known extensions within a typo or two of `name` (`.pyy`), or
+    /// filenames if it has no leading dot (`Makefil`), closest first.
+    /// `name` itself is never suggested.
+    pub fn suggestions(&self, name: &str) -> Vec<String> {
+        let typed = name;
+        let name = name.to_lowercase();
+        let limit = if name.chars().count() <= 4 { 1 } else { 2 };
+        let mut candidates: Vec<(usize, String)> = self
+            .filetypes
+            .iter()
+            .filter(|filetype| filetype.kind.is_none())
+            .flat_map(|filetype| self.names_of(filetype))
+            .filter(|candidate| {
+                !candidate.contains(['*', '/'])
+                    && candidate.starts_with('.') == name.starts_with('.')
+                    && candidate != typed
+            })
+            .filter_map(|candidate| {
+                let distance = strsim::osa_distance(&name, &candidate.to_lowercase());
+                (distance <= limit).then_some((distance, candidate))
+            })
+            .collect();
+        candidates.sort();
+        candidates.dedup_by(|a, b| a.1 == b.1);
+        candidates
+            .into_iter()
+            .map(|(_, candidate)| candidate)
+            .take(3)
+            .collect()
+    }
+
+    pub fn find(&self, name: &str) -> Option<&FileType> {
+        self.filetypes.iter().find(|filetype| filetype.name == name)
+    }
+
+    pub fn by_file_name(&self, file_name: &str) -> Option<&FileType> {
+        self.filetypes
+            .iter()
+            .find(|filetype| filetype.file_names.contains(&file_name))
+    }
+
+    /// The filetype for files with `extension` in `path`'s directory, for
+    /// filetypes that live in a known place like `.github/workflows`.
+    pub fn by_location(&self, path: &Path, extension: &str) -> Option<&FileType> {
+        let parent = path.parent()?;
+        self.filetypes.iter().find(|filetype| {
+            filetype.dir.is_some_and(|dir| {
+                parent.ends_with(dir) && filetype.extensions.contains(&extension)
+            })
+        })
+    }
+
+    /// The filetype for `extension`. Aliases take precedence, so they can
+    /// also remap built-in extensions.
+    pub fn by_extension(&self, extension: &str) -> Option<&FileType> {
+        if let Some((_, name)) = self.aliases.iter().find(|(alias, _)| alias == extension) {
+            return self.find(name);
+        }
+
+        self.filetypes.iter().find(|filetype| {
+            (filetype.dir.is_none() && filetype.extensions.contains(&extension))
+                || (!filetype.is_builtin() && filetype.name == extension)
+        })
+    }
+
+    /// The variant of `base` selected by `--kind`, if it has one.
+    pub fn with_kind<'a>(&'a self, base: &'a FileType, kind: Kind) -> Option<&'a FileType> {
+        if base.base_kind == Some(kind) {
+            return Some(base);
+        }
+        self.filetypes.iter().find(|filetype| {
+            filetype.kind == Some(kind) && filetype.bases.contains(&base.name.as_ref())
+        })
+    }
+
+    /// The variant implied by the file's stem when no `--kind` is given,
+    /// e.g. a pytest file for `test_uart.py`.
+    pub fn with_stem<'a>(&'a self, base: &'a FileType, stem: &str) -> &'a FileType {
+        let test = stem.starts_with("test_") || stem.ends_with("_test");
+        match base.name.as_ref() {
+            "py" if test => self.with_kind(base, Kind::Test).unwrap_or(base),
+            _ => base,
+        }
+    }
+
+    /// Extension and filetype of the file generated alongside `filetype`.
+    pub fn companion(&self, filetype: &FileType) -> Option<(&'static str, &FileType)> {
+        let (extension, name) = filetype.companion?;
+        Some((extension, self.find(name)?))
+    }
+
+    /// The user or shared template rendering `filetype`, if there is one.
+    pub fn user_template(&self, filetype: &FileType) -> Option<PathBuf> {
+        crate::template::find_template(&filetype.name, &self.template_dirs)
+    }
+
+    /// Whether the template rendering `filetype` mentions `variable`, e.g.
+    /// `class_name`. Partials it includes aren't searched.
+    pub fn uses(&self, filetype: &FileType, variable: &str) -> bool {
+        let source = match self.user_template(filetype) {
+            Some(path) => fs::read_to_string(path).unwrap_or_default(),
+            None => filetype.source.to_string(),
+        };
+        let word = |c: char| c.is_alphanumeric() || c == '_';
+        source.match_indices(variable).any(|(start, _)| {
+            let before = source[..start].chars().next_back();
+            let after = source[start + variable.len()..].chars().next();
+            !before.is_some_and(word) && !after.is_some_and(word)
+        })
+    }
+}
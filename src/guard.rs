@@ -0,0 +1,75 @@
+use crate::config::Config;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+/// How C/C++ headers guard against multiple inclusion.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GuardStyle {
+    /// `#ifndef`/`#define`/`#endif` around the header
+    Ifndef,
+    /// A single `#pragma once` line
+    PragmaOnce,
+}
+
+/// Guard settings after merging the command line with the config file.
+#[derive(Debug, Default)]
+pub struct Options {
+    /// `None` leaves the choice to the template: `#ifndef` for `.h`,
+    /// `#pragma once` for `.hpp`.
+    pub style: Option<GuardStyle>,
+    pub prefix: Option<String>,
+    /// Build the macro from the whole path rather than the filename.
+    pub path: bool,
+    pub trailing_underscore: bool,
+}
+
+impl Options {
+    pub fn resolve(style: Option<GuardStyle>, prefix: Option<&String>, config: &Config) -> Options {
+        Options {
+            style: style.or(config.guard_style),
+            prefix: prefix.or(config.guard_prefix.as_ref()).cloned(),
+            path: config.guard_path.unwrap_or(false),
+            trailing_underscore: config.guard_trailing_underscore.unwrap_or(false),
+        }
+    }
+
+    /// The guard macro for `path`, e.g. `UART_H`, or `MYPROJ_SRC_UART_H_`
+    /// with a prefix, the full path and a trailing underscore.
+    pub fn name(&self, path: &Path) -> String {
+        let mut words: Vec<&str> = Vec::new();
+        if let Some(prefix) = &self.prefix {
+            words.push(prefix);
+        }
+        if self.path {
+            words.extend(path.components().filter_map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            }));
+        } else {
+            words.extend(path.file_name().and_then(|name| name.to_str()));
+        }
+
+        let mut guard: String = words
+            .iter()
+            .map(|word| word.trim_matches('_'))
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("_")
+            .chars()
+            .map(|c| match c {
+                c if c.is_ascii_alphanumeric() => c.to_ascii_uppercase(),
+                _ => '_',
+            })
+            .collect();
+
+        if guard.starts_with(|c: char| c.is_ascii_digit()) {
+            guard.insert(0, '_');
+        }
+        if self.trailing_underscore {
+            guard.push('_');
+        }
+        guard
+    }
+}
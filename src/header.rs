@@ -0,0 +1,151 @@
+//! Editing the header banner of existing files.
+
+use crate::{encoding, template::CommentStyle};
+
+/// How many leading lines are searched for an existing header.
+const HEADER_LINES: usize = 20;
+
+/// Whether `contents` already starts with a header like the one tf writes.
+/// Front matter with an author, as tf writes for Markdown, counts as one.
+pub fn has_header(contents: &str) -> bool {
+    let banner = contents
+        .lines()
+        .take(HEADER_LINES)
+        .any(|line| line.contains("Author  :") || line.contains("SPDX-License-Identifier"));
+    banner
+        || front_matter(contents).is_some_and(|front_matter| {
+            front_matter.lines().any(|line| line.starts_with("author:"))
+        })
+}
+
+/// The YAML front matter `contents` starts with, up to and including the
+/// line closing it.
+fn front_matter(contents: &str) -> Option<&str> {
+    let mut lines = contents.split_inclusive('\n');
+    let mut end = lines.next().filter(|line| line.trim_end() == "---")?.len();
+    for line in lines {
+        end += line.len();
+        if matches!(line.trim_end(), "---" | "...") {
+            return Some(&contents[..end]);
+        }
+    }
+    None
+}
+
+/// The comment style of a banner added to `contents` by a filetype whose
+/// banners use `comment`. A docstring banner above an existing module
+/// docstring would take over `__doc__`, so those files get `#` comments.
+pub fn banner_style(contents: &str, comment: CommentStyle) -> CommentStyle {
+    if comment == CommentStyle::DOCSTRING && has_docstring(contents) {
+        CommentStyle::HASHES
+    } else {
+        comment
+    }
+}
+
+/// Whether the first statement of the Python module `contents` is a
+/// string, i.e. its docstring.
+fn has_docstring(contents: &str) -> bool {
+    let (_, contents) = encoding::split_bom(contents);
+    contents
+        .lines()
+        .map(str::trim_start)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| {
+            line.trim_start_matches(['r', 'R', 'u', 'U'])
+                .starts_with(['"', '\''])
+        })
+}
+
+/// Inserts `banner` at the top of `contents`, after any lines that must stay
+/// first: a shebang, Python's `coding` declaration, Ruby's
+/// `frozen_string_literal` comment and YAML front matter. A byte order mark
+/// stays in front of all of them, and the banner gets the line endings of
+/// the first line.
+pub fn insert(contents: &str, banner: &str) -> String {
+    let (bom, contents) = encoding::split_bom(contents);
+    let line_ending = encoding::detect(contents);
+
+    let mut annotated = String::new();
+    let contents = match front_matter(contents) {
+        Some(front_matter) => {
+            annotated.push_str(front_matter);
+            &contents[front_matter.len()..]
+        }
+        None => contents,
+    };
+    let mut lines = contents.split_inclusive('\n').peekable();
+
+    if let Some(shebang) = lines.next_if(|line| line.starts_with("#!")) {
+        annotated.push_str(shebang);
+    }
+    if let Some(coding) = lines.next_if(|line| {
+        line.starts_with('#') && (line.contains("coding:") || line.contains("coding="))
+    }) {
+        annotated.push_str(coding);
+    }
+    if let Some(magic) =
+        lines.next_if(|line| line.starts_with('#') && line.contains("frozen_string_literal:"))
+    {
+        annotated.push_str(magic);
+    }
+
+    let mut added = String::new();
+    if !annotated.is_empty() && !annotated.ends_with('\n') {
+        added.push('\n');
+    }
+    added.push_str(encoding::normalize(banner).trim_end());
+    added.push('\n');
+    if lines.peek().is_some_and(|line| !line.trim().is_empty()) {
+        added.push('\n');
+    }
+    annotated.push_str(&encoding::encode(&added, line_ending, false));
+    annotated.extend(lines);
+    format!("{bom}{annotated}")
+}
+
+/// Sets the banner fields in `values` (e.g. `("File", "uart.c")`) to new
+/// values, leaving every other line alone. Returns `None` if `contents` has
+/// no banner in the `comment` style, or one [`banner_style`] chose for it.
+pub fn update(contents: &str, comment: CommentStyle, values: &[(&str, &str)]) -> Option<String> {
+    let (bom, contents) = encoding::split_bom(contents);
+    let updated = update_banner(contents, comment, values).or_else(|| {
+        let fallback = banner_style(contents, comment);
+        (fallback != comment)
+            .then(|| update_banner(contents, fallback, values))
+            .flatten()
+    })?;
+    Some(format!("{bom}{updated}"))
+}
+
+fn update_banner(contents: &str, comment: CommentStyle, values: &[(&str, &str)]) -> Option<String> {
+    let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+    let is = |line: &str, marker: &str| line.trim_end() == marker;
+
+    let open = lines
+        .iter()
+        .take(HEADER_LINES)
+        .position(|line| is(line, comment.open))?;
+    let close = open
+        + 1
+        + lines[open + 1..]
+            .iter()
+            .take(HEADER_LINES)
+            .position(|line| is(line, comment.close))?;
+
+    let mut updated = String::with_capacity(contents.len());
+    for (index, line) in lines.iter().enumerate() {
+        let field = values.iter().find(|(label, _)| {
+            (open..close).contains(&index)
+                && line.starts_with(&format!("{}{label:<8}:", comment.line))
+        });
+        match field {
+            Some((label, value)) => {
+                let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                updated.push_str(&format!("{}{label:<8}: {value}{ending}", comment.line));
+            }
+            None => updated.push_str(line),
+        }
+    }
+    Some(updated)
+}
@@ -0,0 +1,160 @@
+//! Examples shown after `--help` and in the man pages. Those of `tf new` and
+//! `tf list` come from the built-in filetypes, so they never name a
+//! template that doesn't exist.
+
+use crate::{
+    config::value_name,
+    filetype::{Category, FileType, BUILTINS},
+};
+use clap::Command;
+
+/// Examples of the subcommands that don't depend on the filetypes.
+const EXAMPLES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "config",
+        &[
+            ("tf config init", "write a commented config file"),
+            ("tf config set author \"Jane Doe\"", "set a key"),
+            ("tf config show", "show every value and its source"),
+        ],
+    ),
+    (
+        "template",
+        &[
+            (
+                "tf template add https://git.example.com/hw/sv-templates.git",
+                "share a team's templates",
+            ),
+            ("tf template update", "pull the latest of all of them"),
+        ],
+    ),
+    (
+        "annotate",
+        &[
+            (
+                "tf annotate src/*.c include/*.h",
+                "add headers to old files",
+            ),
+            ("tf annotate -n -l MIT tool.py", "show what would change"),
+        ],
+    ),
+    (
+        "update",
+        &[("tf update rtl/uart_top.sv", "fix the header after a rename")],
+    ),
+    (
+        "undo",
+        &[
+            ("tf undo -n", "show what the last run created"),
+            ("tf undo", "delete it again"),
+        ],
+    ),
+    (
+        "completions",
+        &[(
+            "tf completions zsh > ~/.zfunc/_tf",
+            "install zsh completions",
+        )],
+    ),
+    (
+        "generate-man",
+        &[(
+            "tf generate-man --out-dir man/",
+            "write tf.1 and tf-<command>.1",
+        )],
+    ),
+];
+
+/// `command` with examples after the help of `tf` and its subcommands.
+pub fn with_examples(command: Command) -> Command {
+    let new = examples(&new_examples());
+    let mut command = command.after_help(new.clone());
+    command = command.mut_subcommand("new", |sub| sub.after_help(new));
+    command = command.mut_subcommand("list", |sub| sub.after_help(examples(&list_examples())));
+    for (name, lines) in EXAMPLES {
+        let lines: Vec<(String, String)> = lines
+            .iter()
+            .map(|(example, what)| (example.to_string(), what.to_string()))
+            .collect();
+        command = command.mut_subcommand(name, |sub| sub.after_help(examples(&lines)));
+    }
+    command
+}
+
+/// One file of every category, e.g. `tf uart.sv` for HDL.
+fn new_examples() -> Vec<(String, String)> {
+    Category::ALL
+        .iter()
+        .filter_map(|category| BUILTINS.iter().find(|ft| ft.category == *category))
+        .map(|filetype| {
+            (
+                format!("tf {}", example_args(filetype)),
+                filetype.description.to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Previews of the first built-in filetype and variant.
+fn list_examples() -> Vec<(String, String)> {
+    let mut lines = vec![(String::from("tf list"), String::from("every filetype"))];
+    let base = BUILTINS.iter().find(|ft| ft.kind.is_none());
+    let variant = BUILTINS.iter().find(|ft| ft.kind.is_some());
+    for filetype in base.into_iter().chain(variant) {
+        lines.push((
+            format!("tf list --preview {}", filetype.name),
+            format!("render the {} template", filetype.description),
+        ));
+    }
+    lines
+}
+
+/// How `tf new` is asked for `filetype`, e.g. `uart_driver.sv --kind
+/// uvm-driver`.
+fn example_args(filetype: &FileType) -> String {
+    let stem = match filetype.category {
+        Category::Software => "main",
+        Category::Config => "settings",
+        Category::Docs => "README",
+        _ => "uart",
+    };
+    if let Some(file_name) = filetype.file_names.first() {
+        return file_name.to_string();
+    }
+    if let Some(extension) = filetype.extensions.first() {
+        return match filetype.dir {
+            Some(dir) => format!("{dir}/ci.{extension}"),
+            None => format!("{stem}.{extension}"),
+        };
+    }
+
+    let extension = filetype
+        .bases
+        .iter()
+        .filter_map(|base| BUILTINS.iter().find(|ft| ft.name == *base))
+        .find_map(|base| base.extensions.first())
+        .copied()
+        .unwrap_or_default();
+    match filetype.kind {
+        Some(kind) => {
+            let kind = value_name(&kind);
+            let suffix = kind.trim_start_matches("uvm-");
+            format!("{stem}_{suffix}.{extension} --kind {kind}")
+        }
+        None => format!("{stem}.{extension}"),
+    }
+}
+
+/// An `Examples:` section with the comments lined up.
+fn examples(lines: &[(String, String)]) -> String {
+    let width = lines
+        .iter()
+        .map(|(example, _)| example.len())
+        .max()
+        .unwrap_or(0);
+    let mut text = String::from("Examples:");
+    for (example, what) in lines {
+        text.push_str(&format!("\n  {example:<width$}  # {what}"));
+    }
+    text
+}
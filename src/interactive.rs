@@ -0,0 +1,144 @@
+//! Prompts for what to generate when tf is run without names.
+
+use crate::{
+    filetype::{FileType, Kind, Registry},
+    style,
+};
+use dialoguer::{
+    theme::{ColorfulTheme, SimpleTheme, Theme},
+    Confirm, Input, Select,
+};
+use std::{
+    io::{self, IsTerminal},
+    path::Path,
+};
+
+/// What the user picked: generated like `tf <name> --kind <kind>`.
+pub struct Selection {
+    pub name: String,
+    pub kind: Option<Kind>,
+    pub purpose: Option<String>,
+}
+
+/// Whether there's a terminal to prompt on. Prompts are drawn on stderr.
+pub fn available() -> bool {
+    io::stdin().is_terminal() && io::stderr().is_terminal()
+}
+
+/// Asks for a filetype, a name and a purpose. Returns `None` if the user
+/// backs out.
+pub fn prompt(registry: &Registry) -> Result<Option<Selection>, String> {
+    let theme: Box<dyn Theme> = if style::stderr_colored() {
+        Box::new(ColorfulTheme::default())
+    } else {
+        Box::new(SimpleTheme)
+    };
+    let theme = theme.as_ref();
+
+    let choices: Vec<(&FileType, Vec<String>)> = registry
+        .filetypes
+        .iter()
+        .map(|filetype| (filetype, names(registry, filetype)))
+        .filter(|(_, names)| !names.is_empty())
+        .collect();
+    let width = choices
+        .iter()
+        .map(|(filetype, _)| filetype.description.len())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<String> = choices
+        .iter()
+        .map(|(filetype, _)| {
+            format!(
+                "{:<width$}  {}",
+                filetype.description,
+                filetype.usage(registry)
+            )
+        })
+        .collect();
+
+    let Some(index) = Select::with_theme(theme)
+        .with_prompt("Filetype")
+        .items(&items)
+        .default(0)
+        .max_length(15)
+        .interact_opt()
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    let (filetype, names) = &choices[index];
+
+    // Whole filenames like `Makefile` are offered as they are, paths like
+    // `.github/workflows/*.yml` get the name filled in, and anything else
+    // gets the filetype's extension unless it already has one of them.
+    let name: String = match names.first() {
+        Some(pattern) if pattern.contains('*') => {
+            let name: String = Input::with_theme(theme)
+                .with_prompt(format!("Name (as in {pattern})"))
+                .interact_text()
+                .map_err(|e| e.to_string())?;
+            let stem = filetype
+                .extensions
+                .iter()
+                .find_map(|ext| name.strip_suffix(&format!(".{ext}")))
+                .unwrap_or(&name);
+            pattern.replace('*', stem)
+        }
+        Some(file_name) if !file_name.starts_with('.') => Input::with_theme(theme)
+            .with_prompt("Filename")
+            .default(file_name.clone())
+            .interact_text()
+            .map_err(|e| e.to_string())?,
+        _ => {
+            let name: String = Input::with_theme(theme)
+                .with_prompt(format!("Name (without {})", names[0]))
+                .interact_text()
+                .map_err(|e| e.to_string())?;
+            let has_extension = Path::new(&name)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| names.contains(&format!(".{ext}")));
+            if has_extension {
+                name
+            } else {
+                format!("{name}{}", names[0])
+            }
+        }
+    };
+
+    let purpose: String = Input::with_theme(theme)
+        .with_prompt("Purpose (optional)")
+        .allow_empty(true)
+        .interact_text()
+        .map_err(|e| e.to_string())?;
+
+    let confirmed = Confirm::with_theme(theme)
+        .with_prompt(format!("Create {name} ({})?", filetype.description))
+        .default(true)
+        .interact_opt()
+        .map_err(|e| e.to_string())?;
+    if confirmed != Some(true) {
+        return Ok(None);
+    }
+
+    Ok(Some(Selection {
+        name,
+        kind: filetype.kind.or(filetype.base_kind),
+        purpose: (!purpose.trim().is_empty()).then(|| purpose.trim().to_string()),
+    }))
+}
+
+/// Filenames and extensions that select `filetype`. Variants go through
+/// their bases, since they're generated with `--kind`.
+fn names(registry: &Registry, filetype: &FileType) -> Vec<String> {
+    if filetype.kind.is_none() {
+        return registry.names_of(filetype);
+    }
+    filetype
+        .bases
+        .iter()
+        .filter_map(|base| registry.find(base))
+        .flat_map(|base| registry.names_of(base))
+        .collect()
+}
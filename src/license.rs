@@ -0,0 +1,71 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+
+/// How much license text goes into the header.
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LicenseStyle {
+    /// Only the `SPDX-License-Identifier` line
+    #[default]
+    Short,
+    /// The identifier followed by the license's standard notice
+    Full,
+}
+
+/// Standard notices for the licenses `--license-style full` knows about.
+const NOTICES: &[(&str, &str)] = &[
+    ("MIT", include_str!("../licenses/MIT.txt")),
+    ("Apache-2.0", include_str!("../licenses/Apache-2.0.txt")),
+    ("BSD-3-Clause", include_str!("../licenses/BSD-3-Clause.txt")),
+    ("GPL-3.0-only", include_str!("../licenses/GPL-3.0-only.txt")),
+    (
+        "GPL-3.0-or-later",
+        include_str!("../licenses/GPL-3.0-or-later.txt"),
+    ),
+    ("MPL-2.0", include_str!("../licenses/MPL-2.0.txt")),
+];
+
+/// Deprecated SPDX identifiers that still show up in the wild.
+const DEPRECATED: &[(&str, &str)] = &[
+    ("GPL-3.0", "GPL-3.0-only"),
+    ("GPL-3.0+", "GPL-3.0-or-later"),
+];
+
+/// Checks that `id` looks like an SPDX license expression, e.g. `MIT` or
+/// `MIT OR Apache-2.0`.
+pub fn validate(id: &str) -> Result<(), String> {
+    let valid = !id.trim().is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " -.+()".contains(c));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("'{id}' is not a valid SPDX license identifier"))
+    }
+}
+
+/// Returns the lines of license text placed under the SPDX identifier.
+pub fn notice(id: &str, style: LicenseStyle) -> Result<Vec<String>, String> {
+    if style == LicenseStyle::Short {
+        return Ok(Vec::new());
+    }
+
+    let id = DEPRECATED
+        .iter()
+        .find(|(deprecated, _)| *deprecated == id)
+        .map_or(id, |(_, current)| current);
+
+    NOTICES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(id))
+        .map(|(_, text)| text.lines().map(String::from).collect())
+        .ok_or_else(|| {
+            let known: Vec<&str> = NOTICES.iter().map(|(known, _)| *known).collect();
+            format!(
+                "No full license text for '{id}' (available: {}). Use '--license-style short' instead.",
+                known.join(", ")
+            )
+        })
+}
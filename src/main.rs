@@ -1,391 +1,2025 @@
-use chrono::Utc;
-use clap::Parser;
-use colored::*;
-use std::{error::Error, fs, os::unix::fs::PermissionsExt, process};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::Shell;
+use config::{value_name, Config};
+use error::TfError;
+use filetype::{Category, FileType, Kind, Registry};
+use guard::GuardStyle;
+use license::LicenseStyle;
+use modeline::Modeline;
+use report::{FileReport, OutputFormat, Report};
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+use style::ColorChoice;
+use template::Info;
+
+mod atomic;
+mod config;
+mod cpp;
+mod date;
+mod editor;
+mod editorconfig;
+mod encoding;
+mod error;
+mod filelist;
+mod filetype;
+mod guard;
+mod header;
+mod help;
+mod interactive;
+mod license;
+mod manifest;
+mod modeline;
+mod parallel;
+mod platform;
+mod report;
+mod script;
+mod shared;
+mod style;
+mod template;
+mod undo;
 
 /// Utility for generating files in supported file types
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Name of file to be generated
-    #[arg(required_if_eq("supported_filetypes", "false"))]
-    name: Option<String>,
+    #[command(subcommand)]
+    command: Option<Commands>,
 
-    /// List of supported filetypes
-    #[arg(short, long, default_value_t = false)]
+    /// `tf <file>` is short for `tf new <file>`
+    #[command(flatten)]
+    new: NewArgs,
+
+    /// When to color output [default: auto, which respects NO_COLOR and
+    /// leaves redirected output plain]
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        global = true,
+        default_value_t,
+        hide_default_value = true
+    )]
+    color: ColorChoice,
+
+    /// List of supported filetypes (same as `tf list`, kept for old scripts)
+    #[arg(short, long, default_value_t = false, hide = true)]
     supported_filetypes: bool,
 }
 
-#[derive(Debug, PartialEq)]
-enum FileTypes {
-    C,
-    H,
-    Python,
-    CPP,
-    HPP,
-    Bash,
-    SystemVerilogModule,
-    SystemVerilogPackage,
+/// Options of `tf new`, which are also accepted without the subcommand.
+#[derive(clap::Args, Clone)]
+struct NewArgs {
+    /// Names of files to be generated
+    names: Vec<String>,
+
+    /// Variant of the filetype to generate, for extensions shared by
+    /// several templates (e.g. `tf bus_if.sv --kind interface`)
+    #[arg(short = 'K', long, value_enum)]
+    kind: Option<Kind>,
+
+    /// Shorthand for `--kind tb`: a SystemVerilog testbench for the module
+    /// named by stripping `_tb`/`tb_` from the filename
+    #[arg(long, default_value_t = false, conflicts_with = "kind")]
+    tb: bool,
+
+    /// Shorthand for `--kind class`: a class skeleton named after the file
+    /// in PascalCase (`.cpp` also gets a matching `.hpp`)
+    #[arg(long, default_value_t = false, conflicts_with_all = ["kind", "tb"])]
+    class: bool,
+
+    /// Keep generating the remaining files after one fails
+    #[arg(short, long, default_value_t = false)]
+    keep_going: bool,
+
+    /// Print the rendered files to stdout instead of writing them
+    #[arg(long, default_value_t = false)]
+    stdout: bool,
+
+    /// Fail instead of converting filenames that aren't legal identifiers
+    /// (e.g. `my-uart.sv`) into ones that are (`my_uart`)
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
+    /// Show what would be created without touching the filesystem
+    #[arg(short = 'n', long, default_value_t = false)]
+    dry_run: bool,
+
+    /// Print the result as text or as a JSON object (files, errors, skipped
+    /// names) for editors and scripts
+    #[arg(long, value_enum, default_value_t, conflicts_with = "stdout")]
+    output: OutputFormat,
+
+    /// Print the filetype and permissions of every created file
+    #[arg(short, long, default_value_t = false)]
+    verbose: bool,
+
+    /// Overwrite files that already exist
+    #[arg(short, long, default_value_t = false)]
+    force: bool,
+
+    /// Write into files that already exist but are empty (or only hold
+    /// whitespace), e.g. after `touch` or an editor's "new file" dialog
+    #[arg(long, default_value_t = false, conflicts_with = "force")]
+    fill: bool,
+
+    /// Open the created files in $VISUAL/$EDITOR at the `Purpose` line
+    #[arg(long, default_value_t = false, overrides_with = "no_edit")]
+    edit: bool,
+
+    /// Don't open the created files, even if `auto_edit` is set
+    #[arg(long, default_value_t = false, overrides_with = "edit")]
+    no_edit: bool,
+
+    #[command(flatten)]
+    header: HeaderArgs,
+
+    /// Header guard for `.h`/`.hpp` files [default: ifndef for `.h`,
+    /// pragma-once for `.hpp`]
+    #[arg(long, value_enum)]
+    guard_style: Option<GuardStyle>,
+
+    /// Prefix of `#ifndef` guard macros, e.g. `MYPROJ_`
+    #[arg(long, value_name = "PREFIX")]
+    guard_prefix: Option<String>,
+
+    /// C++ standard the C++ templates target [default: C++17 idioms]
+    #[arg(long, value_enum, value_name = "STD")]
+    std: Option<cpp::Standard>,
+
+    /// Namespace C++ sources and headers are wrapped in, e.g. `acme::uart`
+    #[arg(long, value_name = "NAME")]
+    namespace: Option<String>,
+
+    /// Name of the module, package or entity instead of the file stem, e.g.
+    /// `tf top_wrapper.sv --module-name top`
+    #[arg(long, value_name = "NAME", value_parser = parse_identifier)]
+    module_name: Option<String>,
+
+    /// Name of the class instead of the file stem in PascalCase (or, for
+    /// Java, the file stem)
+    #[arg(long, value_name = "NAME", value_parser = parse_identifier)]
+    class_name: Option<String>,
+
+    /// Append a Vim or Emacs modeline matching the file's indentation
+    #[arg(long, value_enum, overrides_with = "no_modeline")]
+    modeline: Option<Modeline>,
+
+    /// Don't append a modeline, even if `modeline` is set in the config
+    #[arg(long, default_value_t = false, overrides_with = "modeline")]
+    no_modeline: bool,
+
+    /// Ignore `.editorconfig` files instead of following their indentation,
+    /// final newline, line endings and charset
+    #[arg(long, default_value_t = false)]
+    no_editorconfig: bool,
+
+    /// Line endings of the created files [default: lf]
+    #[arg(long, value_enum, value_name = "EOL")]
+    line_endings: Option<encoding::LineEnding>,
+
+    /// Start the created files with a UTF-8 byte order mark, for Windows
+    /// tools that expect one
+    #[arg(long, default_value_t = false, overrides_with = "no_bom")]
+    bom: bool,
+
+    /// Don't write a byte order mark, even if `bom` is set in the config
+    #[arg(long, default_value_t = false, overrides_with = "bom")]
+    no_bom: bool,
+
+    /// Pick the filetype, name and purpose from prompts (the default when
+    /// no names are given on a terminal)
+    #[arg(short, long, default_value_t = false, conflicts_with_all = ["names", "kind", "tb", "class"])]
+    interactive: bool,
+
+    /// Interpreter line of scripts, e.g. `--shebang "/usr/bin/env bash"`
+    #[arg(long, value_name = "INTERPRETER")]
+    shebang: Option<String>,
+
+    /// `set` flags of Bash scripts, e.g. `eu`; empty for none [default: eux]
+    #[arg(long, value_name = "FLAGS")]
+    set_flags: Option<String>,
+
+    /// Make the created files executable (owner execute bit on Unix)
+    #[arg(long, default_value_t = false, overrides_with = "no_executable")]
+    executable: bool,
+
+    /// Don't make the created files executable, even Bash scripts
+    #[arg(long, default_value_t = false, overrides_with = "executable")]
+    no_executable: bool,
+
+    /// Append the created `.sv`/`.svh`/`.v` files to this filelist, creating
+    /// it if needed
+    #[arg(long, value_name = "PATH")]
+    filelist: Option<PathBuf>,
+
+    /// Generate every file listed in a TOML manifest, or none of them if
+    /// any fails to render or already exists
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["names", "interactive", "kind", "tb", "class"])]
+    from_manifest: Option<PathBuf>,
 }
 
-#[derive(Debug)]
-struct Info {
-    date: String,
-    author: String,
-    file: String,
+/// Options controlling what goes into the header, shared by every command
+/// that writes one.
+#[derive(clap::Args, Clone, Default)]
+struct HeaderArgs {
+    /// Author written into the header (overrides $TF_AUTHOR and git config)
+    #[arg(short, long)]
+    author: Option<String>,
+
+    /// Append the git `user.email` to the author
+    #[arg(short, long, default_value_t = false)]
+    email: bool,
+
+    /// Directory of user templates (`<ext>.tera`) overriding the built-ins
+    #[arg(short, long)]
+    template_dir: Option<PathBuf>,
+
+    /// Extra template variable, e.g. `--var purpose="UART driver"` (repeatable)
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+    vars: Vec<(String, String)>,
+
+    /// SPDX license identifier added to the header, e.g. `MIT` or `Apache-2.0`
+    #[arg(short, long, value_parser = parse_license)]
+    license: Option<String>,
+
+    /// Whether to add only the SPDX line or the full license notice
+    #[arg(long, value_enum)]
+    license_style: Option<LicenseStyle>,
+
+    /// Copyright holder, e.g. `--copyright "Acme Corp."` for a
+    /// `Copyright (c) 2025 Acme Corp.` line under the Purpose line
+    #[arg(long, value_name = "HOLDER", overrides_with = "no_copyright")]
+    copyright: Option<String>,
+
+    /// Leave out the copyright line, even if `copyright` is set in the config
+    #[arg(long, default_value_t = false, overrides_with = "copyright")]
+    no_copyright: bool,
+
+    /// Header date format: `us` (01/31/2025), `iso` (2025-01-31) or a
+    /// strftime format such as `%d.%m.%Y`
+    #[arg(long, value_name = "FORMAT")]
+    date_format: Option<String>,
+}
+
+#[derive(Subcommand, Clone)]
+enum Commands {
+    /// Create files from their templates (`tf <file>` does the same)
+    New(Box<NewArgs>),
+    /// Manage the config file
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// List the available filetypes and where their templates come from
+    List {
+        /// Render a template with sample values, by name or extension
+        /// (e.g. `--preview c` or `--preview sv_tb`)
+        #[arg(short, long, value_name = "TEMPLATE")]
+        preview: Option<String>,
+
+        /// Directory of user templates (`<ext>.tera`) overriding the built-ins
+        #[arg(short, long)]
+        template_dir: Option<PathBuf>,
+    },
+    /// Manage shared template repositories
+    #[command(subcommand)]
+    Template(TemplateCommand),
+    /// Add the header banner to existing files that don't have one
+    Annotate {
+        /// Files to annotate
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Show which files would be annotated without changing them
+        #[arg(short = 'n', long, default_value_t = false)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        header: HeaderArgs,
+    },
+    /// Refresh the Author, File and Date lines of existing headers, e.g.
+    /// after renaming a file
+    Update {
+        /// Files to update
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Show which files would change without changing them
+        #[arg(short = 'n', long, default_value_t = false)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        header: HeaderArgs,
+    },
+    /// Delete the files the last `tf new` created, unless they were changed since
+    Undo {
+        /// Show which files would be deleted without deleting them
+        #[arg(short = 'n', long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Print a completion script for `shell`, e.g.
+    /// `tf completions bash > ~/.local/share/bash-completion/completions/tf`
+    Completions { shell: Shell },
+    /// Print the man page, or write a page for `tf` and each command into a
+    /// directory, e.g. for packaging
+    GenerateMan {
+        /// Directory for `tf.1` and `tf-<command>.1` [default: print `tf.1`]
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum TemplateCommand {
+    /// Clone a shared template repository, e.g.
+    /// `tf template add https://git.example.com/hw/sv-templates.git`
+    Add {
+        url: String,
+
+        /// Name of the repository [default: last part of the URL]
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Pull the latest templates of every shared repository, or only `name`
+    Update { name: Option<String> },
+    /// List the shared template repositories
+    List,
+    /// Delete a shared template repository
+    Remove { name: String },
+}
+
+#[derive(Subcommand, Clone)]
+enum ConfigCommand {
+    /// Write a commented default config file
+    Init {
+        /// Overwrite an existing config file
+        #[arg(short, long, default_value_t = false)]
+        force: bool,
+    },
+    /// Set a key in the config file, e.g. `tf config set author "Jane Doe"`
+    Set { key: String, value: String },
+    /// Print the effective configuration and where each value comes from
+    Show,
 }
 
-fn create_file(filename: &str, filetype: FileTypes) -> Result<(), Box<dyn Error>> {
-    let now = Utc::now();
-    let date = now.format("%m/%d/%Y").to_string();
+/// Template settings after merging the command line with the config file.
+struct Settings {
+    guard: guard::Options,
+    script: script::Options,
+    cpp: cpp::Options,
+    /// `{{ ident }}` instead of the one derived from the file stem.
+    module_name: Option<String>,
+    /// `{{ class_name }}` instead of the one derived from the file stem.
+    class_name: Option<String>,
+    modeline: Option<Modeline>,
+    /// Follow the `.editorconfig` files above the generated file.
+    editorconfig: bool,
+    encoding: encoding::Options,
+}
+
+impl Settings {
+    fn resolve(args: &NewArgs, config: &Config) -> Result<Settings, String> {
+        Ok(Settings {
+            guard: guard::Options::resolve(args.guard_style, args.guard_prefix.as_ref(), config),
+            script: script::Options::resolve(
+                args.shebang.as_ref(),
+                args.set_flags.as_ref(),
+                config,
+            ),
+            cpp: cpp::Options::resolve(args.std, args.namespace.as_ref(), config)?,
+            module_name: args.module_name.clone(),
+            class_name: args.class_name.clone(),
+            modeline: args
+                .modeline
+                .or(config.modeline)
+                .filter(|_| !args.no_modeline),
+            editorconfig: !args.no_editorconfig && config.editorconfig.unwrap_or(true),
+            encoding: encoding::Options::resolve(
+                args.line_endings,
+                match (args.bom, args.no_bom) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                },
+                config,
+            ),
+        })
+    }
+
+    /// The settings of the config alone, for files not named on the
+    /// command line.
+    fn from_config(config: &Config) -> Result<Settings, String> {
+        Ok(Settings {
+            guard: guard::Options::resolve(None, None, config),
+            script: script::Options::resolve(None, None, config),
+            cpp: cpp::Options::resolve(None, None, config)?,
+            module_name: None,
+            class_name: None,
+            modeline: config.modeline,
+            editorconfig: config.editorconfig.unwrap_or(true),
+            encoding: encoding::Options::resolve(None, None, config),
+        })
+    }
+}
+
+/// Renders the contents of `path` without touching the filesystem.
+fn render_file(
+    path: &Path,
+    filetype: &FileType,
+    header: &HeaderArgs,
+    settings: &Settings,
+    config: &Config,
+) -> Result<String, TfError> {
+    let info = header_info(path, filetype, header, settings, config)?;
+    let template_dirs = resolve_template_dirs(header.template_dir.as_ref(), config);
+    let contents = template::render(&filetype.name, &info, &template_dirs)?;
+    lay_out(path, filetype, contents, settings).map_err(TfError::Io)
+}
 
-    let mut info = Info {
-        date: date.clone(),
-        file: filename.to_string(),
-        author: env!("LOGNAME", "$LOGNAME isn't defined?").to_string(),
+/// Re-indents `contents` and sets its final newline as `.editorconfig`
+/// says, appends the modeline, and converts the line endings. Templates may
+/// be saved with any line endings.
+fn lay_out(
+    path: &Path,
+    filetype: &FileType,
+    contents: String,
+    settings: &Settings,
+) -> Result<String, String> {
+    let properties = if settings.editorconfig {
+        editorconfig::Properties::of(path)?
+    } else {
+        editorconfig::Properties::default()
     };
+    let contents = encoding::normalize(&contents);
+    let template = editorconfig::Indent::detect(&contents);
+    let indent = properties.indent(filetype.indent_style, template);
 
-    match filetype {
-        FileTypes::C => {
-            let filename_string = format!("{filename}.c");
-            info.file = filename_string;
-            fs::write(&info.file, create_c_file(&info))?;
-        }
-        FileTypes::H => {
-            let filename_string = format!("{filename}.h");
-            info.file = filename_string;
-            fs::write(&info.file, create_h_file(&info))?;
+    let mut contents = match template {
+        Some(template) => editorconfig::reindent(&contents, template, indent),
+        None => contents,
+    };
+    if let Some(modeline) = settings
+        .modeline
+        .filter(|_| filetype.comment.allows_comments())
+    {
+        contents = modeline::append(&contents, &modeline.lines(filetype, indent));
+    }
+    if let Some(insert) = properties.insert_final_newline {
+        contents = editorconfig::final_newline(&contents, insert);
+    }
+    Ok(encoding::encode(
+        &contents,
+        settings.encoding.line_ending(filetype, &properties),
+        settings.encoding.bom(&properties),
+    ))
+}
+
+/// The template variables for creating or annotating `path`.
+fn header_info(
+    path: &Path,
+    filetype: &FileType,
+    header: &HeaderArgs,
+    settings: &Settings,
+    config: &Config,
+) -> Result<Info, TfError> {
+    let Settings {
+        guard, script, cpp, ..
+    } = settings;
+    let file = file_name(path).map_err(TfError::Usage)?;
+    let stem = file_stem(path).map_err(TfError::Usage)?;
+    let (license, license_notice) =
+        resolve_license(header.license.as_ref(), header.license_style, config)
+            .map_err(TfError::Usage)?;
+    let date =
+        date::Options::resolve(header.date_format.as_ref(), config).map_err(TfError::Usage)?;
+    let (copyright, copyright_notice) = resolve_copyright(header, config, &date);
+
+    Ok(Info {
+        date: date.date(),
+        datetime: date.datetime(),
+        file: file.to_string(),
+        stem: stem.to_string(),
+        ident: settings
+            .module_name
+            .clone()
+            .unwrap_or_else(|| identifier(stem)),
+        dir: dir_name(path),
+        guard: guard.name(path),
+        guard_style: guard.style,
+        shebang: script.shebang(filetype),
+        set_flags: script.set_flags(),
+        cpp_std: cpp.standard.map(cpp::Standard::version),
+        namespace: cpp.namespace(),
+        class_name: settings
+            .class_name
+            .clone()
+            .unwrap_or_else(|| class_name(filetype, stem)),
+        author: resolve_author(header, config, existing_dir(path)),
+        comment: filetype.comment,
+        dut: testbench_info(filetype, stem),
+        copyright,
+        copyright_notice,
+        license,
+        license_notice,
+        vars: header.vars.iter().cloned().collect(),
+    })
+}
+
+/// Renders `filetype` for an example file, as shown by `tf list --preview`.
+fn render_preview(
+    filetype: &FileType,
+    registry: &Registry,
+    config: &Config,
+    template_dirs: &[PathBuf],
+) -> Result<String, TfError> {
+    let stem = match filetype.name.as_ref() {
+        "sv_tb" => "example_tb",
+        "py_test" => "test_example",
+        _ => "example",
+    };
+    let file = match filetype.file_names.first() {
+        Some(file_name) => file_name.to_string(),
+        None => {
+            let extension = filetype
+                .extensions
+                .iter()
+                .chain(filetype.bases.iter().filter_map(|base| {
+                    registry.find(base).and_then(|base| base.extensions.first())
+                }))
+                .next()
+                .map_or(filetype.name.as_ref(), |ext| *ext);
+            format!("{stem}.{extension}")
         }
-        FileTypes::Python => {
-            let filename_string = format!("{filename}.py");
-            info.file = filename_string;
-            fs::write(&info.file, create_py_file(&info))?;
+    };
+    let header = HeaderArgs {
+        author: Some(String::from("Jane Doe")),
+        ..Default::default()
+    };
+    let settings = Settings::from_config(config).map_err(TfError::Usage)?;
+    let mut info = header_info(Path::new(&file), filetype, &header, &settings, config)?;
+    info.dir = String::from("project");
+
+    template::render(&filetype.name, &info, template_dirs)
+}
+
+/// The class `filetype` declares in a file called `stem`, unless
+/// `--class-name` says otherwise.
+fn class_name(filetype: &FileType, stem: &str) -> String {
+    if filetype.class_is_stem {
+        identifier(stem)
+    } else {
+        identifier(&pascal_case(stem))
+    }
+}
+
+/// The module under test, for testbenches only.
+fn testbench_info(filetype: &FileType, stem: &str) -> Option<String> {
+    (filetype.name == "sv_tb")
+        .then(|| testbench_dut(&identifier(stem)).map(String::from))
+        .flatten()
+}
+
+/// The SPDX identifier and notice lines for the header, flags first.
+fn resolve_license(
+    flag: Option<&String>,
+    style_flag: Option<LicenseStyle>,
+    config: &Config,
+) -> Result<(Option<String>, Vec<String>), String> {
+    let license = flag.or(config.license.as_ref());
+    let license_style = style_flag.or(config.license_style).unwrap_or_default();
+    let license_notice = match license {
+        Some(id) => {
+            license::validate(id)?;
+            license::notice(id, license_style)?
         }
-        FileTypes::CPP => {
-            let filename_string = format!("{filename}.cpp");
-            info.file = filename_string;
-            fs::write(&info.file, create_cpp_file(&info))?;
+        None => Vec::new(),
+    };
+    Ok((license.cloned(), license_notice))
+}
+
+/// The copyright line and the notice lines under it, unless
+/// `--no-copyright` is given or no holder is set.
+fn resolve_copyright(
+    header: &HeaderArgs,
+    config: &Config,
+    date: &date::Options,
+) -> (Option<String>, Vec<String>) {
+    if header.no_copyright {
+        return (None, Vec::new());
+    }
+    copyright_lines(
+        header.copyright.as_ref().or(config.copyright.as_ref()),
+        config,
+        date,
+    )
+}
+
+/// `Copyright (c) <year> <holder>`, with a range from `copyright_since` to
+/// the year of `date`, and the configured notice.
+fn copyright_lines(
+    holder: Option<&String>,
+    config: &Config,
+    date: &date::Options,
+) -> (Option<String>, Vec<String>) {
+    let Some(holder) = holder else {
+        return (None, Vec::new());
+    };
+    let year = date.year();
+    let years = match config.copyright_since {
+        Some(since) if since < year => format!("{since}-{year}"),
+        _ => year.to_string(),
+    };
+    let notice = config
+        .copyright_notice
+        .iter()
+        .flat_map(|notice| notice.lines())
+        .map(String::from)
+        .collect();
+    (Some(format!("Copyright (c) {years} {holder}")), notice)
+}
+
+/// Directories templates are loaded from, highest precedence first: the
+/// user's (`--template-dir`, then the config file, then
+/// `$XDG_CONFIG_HOME/tf/templates`), then every shared repository.
+fn resolve_template_dirs(flag: Option<&PathBuf>, config: &Config) -> Vec<PathBuf> {
+    let user = flag
+        .cloned()
+        .or_else(|| config.template_dir.clone())
+        .or_else(template::default_template_dir);
+
+    user.into_iter()
+        .chain(
+            shared::repos()
+                .iter()
+                .map(|(_, repo)| shared::template_dir(repo)),
+        )
+        .collect()
+}
+
+/// Permissions for a new file of `filetype`: the config's `permissions`
+/// entry for it or its base filetype, else the built-in default (0744 for
+/// Bash), with the owner execute bit set or every execute bit cleared by
+/// `--executable`/`--no-executable`.
+fn resolve_mode(
+    filetype: &FileType,
+    executable: Option<bool>,
+    config: &Config,
+) -> Result<Option<u32>, String> {
+    let configured = filetype
+        .config_keys()
+        .find_map(|name| config.permissions.get(name).map(|mode| (name, mode)));
+    let mode = match configured {
+        Some((name, mode)) => Some(parse_mode(mode).ok_or_else(|| {
+            format!("permissions: '{mode}' for {name} is not an octal mode like '755'.")
+        })?),
+        None => filetype.mode,
+    };
+
+    Ok(match executable {
+        Some(true) => Some(mode.unwrap_or(0o644) | 0o100),
+        Some(false) => mode.map(|mode| mode & !0o111),
+        None => mode,
+    })
+}
+
+/// Parses `755`, `0755` or `0o755`.
+fn parse_mode(mode: &str) -> Option<u32> {
+    let digits = mode.trim().trim_start_matches("0o");
+    u32::from_str_radix(digits, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o7777)
+}
+
+/// Whether `path` is a file holding nothing but whitespace.
+fn is_blank(path: &Path) -> bool {
+    fs::read(path).is_ok_and(|contents| contents.iter().all(u8::is_ascii_whitespace))
+}
+
+fn create_file(path: &Path, mode: Option<u32>, contents: &str) -> Result<(), TfError> {
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)?;
+
+    if let Some(mode) = mode {
+        platform::set_mode(path, mode)?;
+    }
+
+    Ok(())
+}
+
+/// Converts a snake_case or kebab-case stem to PascalCase, leaving the case
+/// of everything but word starts alone (`uart_driver` -> `UartDriver`).
+fn pascal_case(stem: &str) -> String {
+    stem.split(['_', '-'])
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars))
+                .into_iter()
+                .flatten()
+        })
+        .collect()
+}
+
+/// Turns `stem` into an identifier that's legal in every supported language:
+/// runs of characters other than ASCII letters, digits and `_` become a
+/// single `_`, and a leading digit gets a `_` in front (`my-uart` ->
+/// `my_uart`, `2d_fft` -> `_2d_fft`).
+fn identifier(stem: &str) -> String {
+    let mut ident = String::with_capacity(stem.len());
+    for c in stem.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            ident.push(c);
+        } else if !ident.ends_with('_') {
+            ident.push('_');
         }
-        FileTypes::HPP => {
-            let filename_string = format!("{filename}.hpp");
-            info.file = filename_string;
-            fs::write(&info.file, create_hpp_file(&info))?;
+    }
+
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    ident
+}
+
+/// Name of the module a testbench file exercises: `uart_tb` and `tb_uart`
+/// both test `uart`.
+fn testbench_dut(stem: &str) -> Option<&str> {
+    stem.strip_suffix("_tb")
+        .or_else(|| stem.strip_prefix("tb_"))
+        .filter(|dut| !dut.is_empty())
+}
+
+/// Closest existing directory containing `path`, so git can be queried
+/// before missing directories are created.
+fn existing_dir(path: &Path) -> &Path {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .unwrap_or(Path::new("."))
+}
+
+/// Resolves the author at runtime, in order of precedence: `--author`,
+/// `$TF_AUTHOR`, the config file, `git config user.name` (run from the target
+/// directory so per-repo identities are honored), then `$USER`/`$LOGNAME`
+/// (`$USERNAME` on Windows).
+fn resolve_author(header: &HeaderArgs, config: &Config, dir: &Path) -> String {
+    let (author, _) = author_with_source(header.author.as_deref(), config, dir);
+
+    match header
+        .email
+        .then(|| git_config(dir, "user.email"))
+        .flatten()
+    {
+        Some(email) => format!("{author} <{email}>"),
+        None => author,
+    }
+}
+
+/// The author as resolved by [`resolve_author`], and where it came from.
+fn author_with_source(flag: Option<&str>, config: &Config, dir: &Path) -> (String, &'static str) {
+    flag.map(|author| (author.to_string(), "--author"))
+        .or_else(|| non_empty_var("TF_AUTHOR").map(|author| (author, "$TF_AUTHOR")))
+        .or_else(|| config.author.clone().map(|author| (author, "config file")))
+        .or_else(|| git_config(dir, "user.name").map(|author| (author, "git config")))
+        .or_else(|| non_empty_var("USER").map(|author| (author, "$USER")))
+        .or_else(|| non_empty_var("LOGNAME").map(|author| (author, "$LOGNAME")))
+        .or_else(|| non_empty_var("USERNAME").map(|author| (author, "$USERNAME")))
+        .unwrap_or_else(|| (String::from("Unknown"), "default"))
+}
+
+fn non_empty_var(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn git_config(dir: &Path, key: &str) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["config", "--get", key])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Parses a `--var` argument of the form `key=value`.
+fn parse_var(input: &str) -> Result<(String, String), String> {
+    let (key, value) = input
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, found '{input}'"))?;
+
+    let mut chars = key.chars();
+    let valid_start = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+    if !valid_start || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("'{key}' is not a valid variable name"));
+    }
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+fn parse_identifier(input: &str) -> Result<String, String> {
+    if identifier(input) == input {
+        Ok(input.to_string())
+    } else {
+        Err(format!(
+            "'{input}' is not a legal identifier, e.g. '{}'",
+            identifier(input)
+        ))
+    }
+}
+
+fn parse_license(input: &str) -> Result<String, String> {
+    license::validate(input)?;
+    Ok(input.to_string())
+}
+
+fn file_name(path: &Path) -> Result<&str, String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| String::from("Input filename is expected."))
+}
+
+fn file_stem(path: &Path) -> Result<&str, String> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| String::from("Input filename is expected."))
+}
+
+fn check_input_errs<'a>(path: &'a Path, registry: &Registry) -> Result<&'a str, TfError> {
+    let name = file_name(path).map_err(TfError::Usage)?;
+    file_stem(path).map_err(TfError::Usage)?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if !ext.is_empty() => Ok(ext),
+        _ if name
+            .strip_prefix('.')
+            .is_some_and(|ext| registry.by_extension(ext).is_some()) =>
+        {
+            Err(TfError::Unsupported(format!(
+                "Filename is expected before the extension, e.g. 'name{name}'."
+            )))
         }
-        FileTypes::Bash => {
-            let filename_string = format!("{filename}.bash");
-            info.file = filename_string;
-            fs::write(&info.file, create_bash_file(&info))?;
-            let mut perms = fs::metadata(&info.file)?.permissions();
-            perms.set_mode(0o744);
-            fs::set_permissions(&info.file, perms)?;
+        _ => Err(TfError::Unsupported(format!(
+            "Filename with file extension is expected.{}",
+            did_you_mean(registry, name)
+        ))),
+    }
+}
+
+/// ` Did you mean 'Makefile'?` if `name` is close to a known filename or
+/// extension, else nothing.
+fn did_you_mean(registry: &Registry, name: &str) -> String {
+    let quoted: Vec<String> = registry
+        .suggestions(name)
+        .iter()
+        .map(|suggestion| format!("'{suggestion}'"))
+        .collect();
+    match quoted.split_last() {
+        None => String::new(),
+        Some((last, [])) => format!(" Did you mean {last}?"),
+        Some((last, rest)) => format!(" Did you mean {} or {last}?", rest.join(", ")),
+    }
+}
+
+/// Detects the filetype from special filenames like `Makefile` first,
+/// then from the directory and extension (`.github/workflows/*.yml`), then
+/// from the extension alone.
+fn detect_filetype<'a>(path: &Path, registry: &'a Registry) -> Result<&'a FileType, TfError> {
+    if let Some(filetype) = registry.by_file_name(file_name(path).map_err(TfError::Usage)?) {
+        return Ok(filetype);
+    }
+
+    let extension = check_input_errs(path, registry)?;
+    if let Some(filetype) = registry.by_location(path, extension) {
+        return Ok(filetype);
+    }
+    registry.by_extension(extension).ok_or_else(|| {
+        TfError::Unsupported(format!(
+            "Filetype '.{extension}' is not supported.{} Run 'tf list' for available filetypes.",
+            did_you_mean(registry, &format!(".{extension}"))
+        ))
+    })
+}
+
+/// Name of the directory `path` is (or will be) created in.
+fn dir_name(path: &Path) -> String {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    parent
+        .canonicalize()
+        .or_else(|_| std::path::absolute(parent))
+        .ok()
+        .and_then(|dir| {
+            dir.file_name()
+                .and_then(|name| name.to_str())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| String::from("project"))
+}
+
+/// Writes every filetype grouped by category, with how to generate it and
+/// whether its template is built in or comes from the template dir.
+fn show_filetypes(registry: &Registry, out: &mut impl Write) -> io::Result<()> {
+    let width = registry
+        .filetypes
+        .iter()
+        .map(|filetype| filetype.description.len())
+        .max()
+        .unwrap_or_default();
+    let usages: Vec<String> = registry
+        .filetypes
+        .iter()
+        .map(|filetype| filetype.usage(registry))
+        .collect();
+    let usage_width = usages.iter().map(String::len).max().unwrap_or_default();
+
+    let mut first = true;
+    for category in Category::ALL {
+        let entries: Vec<_> = registry
+            .filetypes
+            .iter()
+            .zip(&usages)
+            .filter(|(filetype, _)| filetype.category == category)
+            .collect();
+        if entries.is_empty() {
+            continue;
         }
-        FileTypes::SystemVerilogModule => {
-            let filename_string = format!("{filename}.sv");
-            info.file = filename_string;
-            fs::write(&info.file, create_sv_file(&info))?;
+
+        if !first {
+            writeln!(out)?;
         }
-        FileTypes::SystemVerilogPackage => {
-            let filename_string = format!("{filename}.svh");
-            info.file = filename_string;
-            fs::write(&info.file, create_svh_file(&info))?;
+        first = false;
+        writeln!(out, "{}", style::heading(category.heading()))?;
+        for (filetype, usage) in entries {
+            let source = match registry.user_template(filetype) {
+                None => String::from("built-in"),
+                Some(path) => {
+                    let origin = path
+                        .parent()
+                        .and_then(shared::repo_name)
+                        .map_or_else(|| String::from("user"), |repo| format!("shared: {repo}"));
+                    if filetype.is_builtin() {
+                        style::notice(&format!("{origin} override"))
+                    } else {
+                        style::notice(&origin)
+                    }
+                }
+            };
+            writeln!(
+                out,
+                "  {} : {} {source}",
+                style::key(&format!("{:<width$}", filetype.description)),
+                style::value(&format!("{usage:<usage_width$}")),
+            )?;
         }
     }
-
     Ok(())
 }
 
-fn check_input_errs(input: &Vec<&str>) -> Result<(), String> {
-    if *input == [""] {
-        return Err(String::from("Input filename is expected."));
+/// Looks up a filetype by template name, extension or filename, as for
+/// `tf list --preview` and a manifest's `type`.
+fn find_filetype<'a>(registry: &'a Registry, name: &str) -> Result<&'a FileType, String> {
+    let name = name.trim_start_matches('.');
+    registry
+        .find(name)
+        .or_else(|| registry.by_extension(name))
+        .or_else(|| registry.by_file_name(name))
+        .ok_or_else(|| {
+            format!("No template named '{name}'. Run 'tf list' for available filetypes.")
+        })
+}
+
+fn run_list_command(preview: Option<String>, template_dir: Option<PathBuf>) -> Result<(), TfError> {
+    let config = config::load()?;
+    let template_dirs = resolve_template_dirs(template_dir.as_ref(), &config);
+    let registry = Registry::load(&template_dirs, &config.aliases).map_err(TfError::Usage)?;
+
+    match preview {
+        Some(name) => {
+            let filetype = find_filetype(&registry, &name).map_err(TfError::Unsupported)?;
+            let preview = render_preview(filetype, &registry, &config, &template_dirs)
+                .map_err(|e| e.context(format!("rendering '{}'", filetype.name)))?;
+            print_out(io::stdout().lock().write_all(preview.as_bytes()))
+        }
+        None => print_out(show_filetypes(&registry, &mut io::stdout().lock())),
     }
-    if input.len() <= 1 {
-        return Err(String::from("Filename with file extension is expected."));
+}
+
+/// Adds the header banner to every file in `files` that lacks one. Files
+/// are checked and rendered before any of them is changed.
+fn run_annotate_command(
+    files: &[PathBuf],
+    dry_run: bool,
+    header: &HeaderArgs,
+) -> Result<(), TfError> {
+    let config = config::load()?;
+    let template_dirs = resolve_template_dirs(header.template_dir.as_ref(), &config);
+    let registry = Registry::load(&template_dirs, &config.aliases).map_err(TfError::Usage)?;
+
+    let mut annotated = Vec::new();
+    for path in files {
+        let name = path.display();
+        let filetype = detect_filetype(path, &registry).map_err(|e| e.context(&name))?;
+        let contents = fs::read_to_string(path).map_err(|e| TfError::from(e).context(&name))?;
+
+        if !filetype.comment.allows_comments() {
+            println!(
+                "{} {name} (the format has no comments)",
+                style::notice("SKIPPED")
+            );
+            continue;
+        }
+        if header::has_header(&contents) {
+            println!("{} {name} (already has a header)", style::notice("SKIPPED"));
+            continue;
+        }
+
+        let settings = Settings::from_config(&config).map_err(TfError::Usage)?;
+        let mut info = header_info(path, filetype, header, &settings, &config)?;
+        info.comment = header::banner_style(&contents, filetype.comment);
+        let banner = template::render_banner(&info, &template_dirs)
+            .map_err(|e| e.context(format!("{name}: rendering banner")))?;
+        annotated.push((path, header::insert(&contents, &banner)));
     }
 
+    for (path, contents) in annotated {
+        if dry_run {
+            println!("{} {}", style::planned("WOULD ANNOTATE"), path.display());
+        } else {
+            fs::write(path, contents).map_err(|e| TfError::from(e).context(path.display()))?;
+            println!("{} {}", style::done("ANNOTATED"), path.display());
+        }
+    }
     Ok(())
 }
 
-fn show_supported_filetypes() {
-    println!("{}", "Software Filetypes:".bright_cyan().bold().underline());
-    println!(
-        "  {}      : '{}'",
-        "C".bright_cyan().bold(),
-        ".c".bright_green().bold()
-    );
-    println!(
-        "  {}      : '{}'",
-        "H".bright_cyan().bold(),
-        ".h".bright_green().bold()
-    );
-    println!(
-        "  {} : '{}'",
-        "Python".bright_cyan().bold(),
-        ".py".bright_green().bold()
-    );
-    println!(
-        "  {}    : '{}'",
-        "CPP".bright_cyan().bold(),
-        ".cpp".bright_green().bold()
-    );
-    println!(
-        "  {}    : '{}'",
-        "HPP".bright_cyan().bold(),
-        ".hpp".bright_green().bold()
-    );
-    println!(
-        "  {}   : '{}'",
-        "Bash".bright_cyan().bold(),
-        ".bash".bright_green().bold()
-    );
-    println!("");
-    println!("{}", "HDL Filetypes:".bright_cyan().bold().underline());
-    println!(
-        "  {}  : '{}'",
-        "SystemVerilog (module)".bright_cyan().bold(),
-        ".sv".bright_green().bold()
-    );
-    println!(
-        "  {} : '{}'",
-        "SystemVerilog (package)".bright_cyan().bold(),
-        ".svh".bright_green().bold()
-    );
-    process::exit(0)
-}
-
-fn main() -> Result<(), Box<dyn Error>> {
-    let args = Args::parse();
-
-    if args.supported_filetypes {
-        show_supported_filetypes()
-    }
-
-    let input_filename = args.name.unwrap_or_else(|| {
-        eprintln!(
-            "{}: Program requires argument. See help with 'tf --help'",
-            "ERROR".red()
-        );
-        process::exit(1)
-    });
+/// Rewrites the Author, File and Date lines of the banner in every file in
+/// `files`. Files without a banner are skipped.
+fn run_update_command(
+    files: &[PathBuf],
+    dry_run: bool,
+    header: &HeaderArgs,
+) -> Result<(), TfError> {
+    let config = config::load()?;
+    let template_dirs = resolve_template_dirs(header.template_dir.as_ref(), &config);
+    let registry = Registry::load(&template_dirs, &config.aliases).map_err(TfError::Usage)?;
+    let today = date::Options::resolve(header.date_format.as_ref(), &config)
+        .map_err(TfError::Usage)?
+        .date();
 
-    let filename: Vec<&str> = input_filename.split(".").collect();
+    let mut updates = Vec::new();
+    for path in files {
+        let name = path.display();
+        let filetype = detect_filetype(path, &registry).map_err(|e| e.context(&name))?;
+        let contents = fs::read_to_string(path).map_err(|e| TfError::from(e).context(&name))?;
+        if !filetype.comment.allows_comments() {
+            println!(
+                "{} {name} (the format has no comments)",
+                style::notice("SKIPPED")
+            );
+            continue;
+        }
 
-    if let Err(msg) = check_input_errs(&filename) {
-        eprintln!("{} with input: {msg}", "ERROR".red());
-        process::exit(1);
+        let author = resolve_author(header, &config, existing_dir(path));
+        let values = [
+            ("Author", author.as_str()),
+            ("File", file_name(path).map_err(TfError::Usage)?),
+            ("Date", &today),
+        ];
+        match header::update(&contents, filetype.comment, &values) {
+            Some(updated) if updated == contents => {
+                println!("{} {name} (up to date)", style::notice("SKIPPED"));
+            }
+            Some(updated) => updates.push((path, updated)),
+            None => println!("{} {name} (no tf header)", style::notice("SKIPPED")),
+        }
     }
 
-    let filetype: FileTypes = match filename.last() {
-        Some(&"c") => FileTypes::C,
-        Some(&"h") => FileTypes::H,
-        Some(&"py") => FileTypes::Python,
-        Some(&"cpp") => FileTypes::CPP,
-        Some(&"hpp") => FileTypes::HPP,
-        Some(&"bash") => FileTypes::Bash,
-        Some(&"sv") => FileTypes::SystemVerilogModule,
-        Some(&"svh") => FileTypes::SystemVerilogPackage,
-        Some(&unsupported_filetype) => {
-            eprintln!("{}: Filetype '.{unsupported_filetype}' is not supported. Run 'tf --list-filetypes' for available filetypes.", "ERROR".red());
-            process::exit(1)
+    for (path, contents) in updates {
+        if dry_run {
+            println!("{} {}", style::planned("WOULD UPDATE"), path.display());
+        } else {
+            fs::write(path, contents).map_err(|e| TfError::from(e).context(path.display()))?;
+            println!("{} {}", style::done("UPDATED"), path.display());
+        }
+    }
+    Ok(())
+}
+
+/// A rendered file waiting to be written.
+struct Planned<'a> {
+    path: PathBuf,
+    filetype: &'a FileType,
+    /// Permissions to set, if not the default.
+    mode: Option<u32>,
+    contents: String,
+}
+
+/// Validates `name` and renders it and its companion files, with
+/// `filetype` instead of the detected one if given. Warnings are added to
+/// `warnings` rather than printed, so plans made in parallel can report
+/// them in order.
+fn plan<'a>(
+    name: &str,
+    filetype: Option<&'a FileType>,
+    args: &NewArgs,
+    config: &Config,
+    registry: &'a Registry,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<Planned<'a>>, TfError> {
+    let path = Path::new(name);
+    let stem = file_stem(path).map_err(TfError::Usage)?;
+
+    let mut filetype = match filetype {
+        Some(filetype) => filetype,
+        None => detect_filetype(path, registry)?,
+    };
+
+    let kind = if args.tb {
+        Some(Kind::Tb)
+    } else if args.class {
+        Some(Kind::Class)
+    } else {
+        args.kind
+    };
+    match kind {
+        Some(kind) => {
+            filetype = registry.with_kind(filetype, kind).ok_or_else(|| {
+                TfError::Usage(format!(
+                    "Kind '{}' doesn't apply to {} files.",
+                    value_name(&kind),
+                    filetype.description
+                ))
+            })?;
         }
         None => {
-            panic!("Why are you the way that you are? :(");
+            let by_stem = registry.with_stem(filetype, stem);
+            filetype = match config.default_kinds.get(filetype.name.as_ref()) {
+                Some(&kind) if by_stem.name == filetype.name => {
+                    registry.with_kind(filetype, kind).ok_or_else(|| {
+                        TfError::Other(format!(
+                            "Kind '{}' from default_kinds doesn't apply to {} files.",
+                            value_name(&kind),
+                            filetype.description
+                        ))
+                    })?
+                }
+                _ => by_stem,
+            };
         }
-    };
+    }
+
+    let ident = identifier(stem);
+    let renamed = args.module_name.is_some() || args.class_name.is_some();
+    if filetype.stem_is_identifier && ident != stem && !renamed {
+        if args.strict {
+            return Err(TfError::Usage(format!(
+                "'{stem}' is not a legal identifier. Rename the file, e.g. to '{ident}', or drop '--strict'."
+            )));
+        }
+        warnings.push(format!(
+            "{name}: '{stem}' is not a legal identifier, using '{ident}'"
+        ));
+    }
+
+    if filetype.name == "sv_tb" && testbench_dut(&ident).is_none() {
+        return Err(TfError::Usage(String::from(
+            "Testbench names are expected to look like 'uart_tb.sv' or 'tb_uart.sv'.",
+        )));
+    }
+
+    let mut files = vec![(path.to_path_buf(), filetype)];
+    if let Some((extension, companion)) = registry.companion(filetype) {
+        files.push((path.with_extension(extension), companion));
+    }
+    let overrides = [
+        ("--module-name", "ident", &args.module_name),
+        ("--class-name", "class_name", &args.class_name),
+    ];
+    for (flag, variable, _) in overrides.iter().filter(|(_, _, name)| name.is_some()) {
+        if !files
+            .iter()
+            .any(|(_, filetype)| registry.uses(filetype, variable))
+        {
+            warnings.push(format!(
+                "{name}: {} files don't use '{flag}', ignoring it",
+                filetype.description
+            ));
+        }
+    }
+
+    if !args.stdout && !args.force {
+        let fillable = |path: &Path| args.fill && is_blank(path);
+        if let Some((existing, _)) = files
+            .iter()
+            .find(|(path, _)| path.exists() && !fillable(path))
+        {
+            return Err(exists_error(existing, args));
+        }
+    }
+
+    let settings = Settings::resolve(args, config).map_err(TfError::Usage)?;
+    let rendered = files
+        .iter()
+        .map(|(path, filetype)| render_file(path, filetype, &args.header, &settings, config))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.context("rendering file"))?;
 
-    if let Err(e) = create_file(filename.first().unwrap(), filetype) {
-        eprintln!("{} creating file: {e}", "ERROR".red());
-        process::exit(1);
+    let executable = match (args.executable, args.no_executable) {
+        (true, _) => Some(true),
+        (_, true) => Some(false),
+        _ => None,
     };
+    files
+        .into_iter()
+        .zip(rendered)
+        .map(|((path, filetype), contents)| {
+            Ok(Planned {
+                mode: resolve_mode(filetype, executable, config).map_err(TfError::Usage)?,
+                path,
+                filetype,
+                contents,
+            })
+        })
+        .collect()
+}
 
-    Ok(())
+/// Why `path`, which exists, can't be written without `--force`.
+fn exists_error(path: &Path, args: &NewArgs) -> TfError {
+    TfError::Exists(if args.fill {
+        format!(
+            "'{}' isn't empty. '--fill' only writes into empty files.",
+            path.display()
+        )
+    } else if is_blank(path) {
+        format!(
+            "'{}' already exists but is empty. Use '--fill' to write into it.",
+            path.display()
+        )
+    } else {
+        format!(
+            "'{}' already exists. Use '--force' to overwrite it.",
+            path.display()
+        )
+    })
 }
 
-fn create_c_file(info: &Info) -> String {
-    String::from(format!(
-        "////////////////////////////////////////////////////////////////////////
-// Author  : {}
-// File    : {}
-// Date    : {}
-// Purpose : TODO
-////////////////////////////////////////////////////////////////////////
+/// Writes all of `planned` or, if a file can't be written, none of it.
+/// With `--stdout` it's printed instead. Returns the files that didn't
+/// exist before, which are the ones `tf undo` may delete.
+fn write(planned: &[Planned], args: &NewArgs) -> Result<Vec<PathBuf>, TfError> {
+    if args.stdout {
+        let mut stdout = io::stdout().lock();
+        for file in planned {
+            print_out(stdout.write_all(file.contents.as_bytes()))?;
+        }
+        return Ok(Vec::new());
+    }
+    if args.dry_run {
+        return Ok(Vec::new());
+    }
 
-#include <stdio.h>
+    let files: Vec<atomic::File> = planned
+        .iter()
+        .map(|file| atomic::File {
+            path: &file.path,
+            mode: file.mode,
+            contents: &file.contents,
+        })
+        .collect();
+    atomic::write_all(&files).map_err(TfError::Io)
+}
 
-int main(int argc, char *argv[]) {{
-  printf(\"Hello, World!\\n\");
-  return 0;
-}}
+/// The outcome of writing to stdout. A reader that stopped early (`tf
+/// --stdout ... | head`) ends tf quietly and successfully.
+fn print_out(result: io::Result<()>) -> Result<(), TfError> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => process::exit(0),
+        result => result.map_err(|e| TfError::Io(format!("writing to stdout: {e}"))),
+    }
+}
 
-",
-        info.author, info.file, info.date,
-    ))
+fn print_warnings(warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("{} {warning}", style::warning("WARNING"));
+    }
 }
 
-fn create_h_file(info: &Info) -> String {
-    let guard = info.file.replace(".", "_").to_uppercase();
-    String::from(format!(
-        "////////////////////////////////////////////////////////////////////////
-// Author  : {}
-// File    : {}
-// Date    : {}
-// Purpose : TODO
-////////////////////////////////////////////////////////////////////////
+/// Errors of a manifest, by the entry (or the manifest) they belong to.
+type ManifestErrors = Vec<(String, TfError)>;
 
-#ifndef {guard}
-#define {guard}
+/// Renders every file in the manifest at `path`, in parallel, and writes
+/// them only if all of them render and none would overwrite another file.
+/// Returns the files and those of them that are new, or the errors by
+/// file.
+fn generate_manifest<'a>(
+    path: &Path,
+    args: &NewArgs,
+    config: &Config,
+    registry: &'a Registry,
+) -> Result<(Vec<Planned<'a>>, Vec<PathBuf>), ManifestErrors> {
+    let manifest = manifest::load(path).map_err(|e| vec![(path.display().to_string(), e)])?;
 
-// STRUCTS
+    let plans = parallel::map(&manifest.files, |entry| {
+        let mut entry_args = args.clone();
+        entry_args.kind = entry.kind;
+        entry_args.header.vars = manifest
+            .vars
+            .iter()
+            .chain(&entry.vars)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .chain(args.header.vars.iter().cloned())
+            .collect();
 
-// FUNCTIONS
+        let mut warnings = Vec::new();
+        let files = entry
+            .filetype
+            .as_deref()
+            .map(|name| find_filetype(registry, name).map_err(TfError::Unsupported))
+            .transpose()
+            .and_then(|filetype| {
+                plan(
+                    &entry.path,
+                    filetype,
+                    &entry_args,
+                    config,
+                    registry,
+                    &mut warnings,
+                )
+            });
+        (warnings, files)
+    });
 
-////////////////////////////////////////////////////////////////////////
-#endif
-",
-        info.author, info.file, info.date,
-    ))
-}
+    let mut planned: Vec<Planned> = Vec::new();
+    let mut errors = Vec::new();
+    for (entry, (warnings, files)) in manifest.files.iter().zip(plans) {
+        print_warnings(&warnings);
+        match files {
+            Ok(files) => {
+                for file in files {
+                    if planned.iter().any(|other| other.path == file.path) {
+                        errors.push((
+                            entry.path.clone(),
+                            TfError::Usage(format!(
+                                "'{}' is listed more than once.",
+                                file.path.display()
+                            )),
+                        ));
+                    }
+                    planned.push(file);
+                }
+            }
+            Err(msg) => errors.push((entry.path.clone(), msg)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
 
-fn create_py_file(info: &Info) -> String {
-    String::from(format!(
-        "\"\"\"
-Author  : {}
-File    : {}
-Date    : {}
-Purpose : TODO
-\"\"\"
+    let new = write(&planned, args).map_err(|e| vec![(path.display().to_string(), e)])?;
+    Ok((planned, new))
+}
 
+/// Adds the HDL files among `created` to the filelist at `path`, creating
+/// it from the `.f` template first if needed. Returns the new entries.
+fn update_filelist(
+    path: &Path,
+    created: &[PathBuf],
+    args: &NewArgs,
+    config: &Config,
+    registry: &Registry,
+) -> Result<Vec<String>, TfError> {
+    let entries = filelist::missing_entries(path, created);
+    if args.dry_run || entries.is_empty() {
+        return Ok(entries);
+    }
 
-def main() -> int:
-    return 0
+    let name = path.display();
+    if !path.exists() {
+        let filetype = registry
+            .find("f")
+            .ok_or_else(|| TfError::Unsupported(String::from("No filelist template")))?;
+        let settings =
+            Settings::from_config(config).map_err(|e| TfError::Usage(format!("{name}: {e}")))?;
+        let contents = render_file(path, filetype, &args.header, &settings, config)
+            .map_err(|e| e.context(format!("{name}: rendering file")))?;
+        let mode = resolve_mode(filetype, None, config)
+            .map_err(|e| TfError::Usage(format!("{name}: {e}")))?;
+        create_file(path, mode, &contents).map_err(|e| e.context(&name))?;
+    }
+    filelist::append(path, &entries).map_err(|e| TfError::from(e).context(&name))?;
+    Ok(entries)
+}
 
+/// Prints what happened to `path` after it was generated successfully.
+fn report_success(file: &Planned, args: &NewArgs, multiple: bool) {
+    let (path, filetype) = (&file.path, file.filetype);
+    let name = path.display();
 
-if __name__ == \"__main__\":
-    main()",
-        info.author, info.file, info.date,
-    ))
+    if args.dry_run {
+        let mode = platform::planned_permissions(file.mode);
+        println!(
+            "{} {name} ({}, permissions: {mode})",
+            style::planned("WOULD CREATE"),
+            filetype.description
+        );
+    } else if args.verbose {
+        let mode = platform::permissions(path).unwrap_or_else(|_| String::from("unknown"));
+        println!(
+            "{} {name} ({}, permissions: {mode})",
+            style::done("CREATED"),
+            filetype.description
+        );
+    } else if multiple {
+        println!("{} {name}", style::done("CREATED"));
+    }
 }
 
-fn create_cpp_file(info: &Info) -> String {
-    String::from(format!(
-        "////////////////////////////////////////////////////////////////////////
-// Author  : {}
-// File    : {}
-// Date    : {}
-// Purpose : TODO
-////////////////////////////////////////////////////////////////////////
+/// Writes every setting's effective value and where it comes from.
+fn show_config(config: &Config, out: &mut impl Write) -> io::Result<()> {
+    let path = config::config_path();
+    let status = match &path {
+        Some(path) if path.is_file() => String::new(),
+        Some(_) => String::from(" (not found)"),
+        None => String::from(" (no config directory)"),
+    };
+    let path = path.map_or_else(|| String::from("-"), |path| path.display().to_string());
+    writeln!(out, "{} {path}{status}", style::key("Config file:"))?;
+    writeln!(out)?;
 
-#include <iostream>
+    let from_file = |value: Option<String>, default: Option<String>| match (value, default) {
+        (Some(value), _) => (value, "config file"),
+        (None, Some(default)) => (default, "default"),
+        (None, None) => (String::from("-"), "unset"),
+    };
 
-int main(int argc, char *argv[]) {{
-  std::cout << \"Hello, World!\" << std::endl;
-  return 0;
-}}
+    let (author, author_source) = author_with_source(None, config, Path::new("."));
+    let settings = [
+        ("author", (author, author_source)),
+        ("license", from_file(config.license.clone(), None)),
+        (
+            "license_style",
+            from_file(
+                config.license_style.as_ref().map(value_name),
+                Some(value_name(&LicenseStyle::default())),
+            ),
+        ),
+        (
+            "guard_style",
+            from_file(config.guard_style.as_ref().map(value_name), None),
+        ),
+        ("copyright", from_file(config.copyright.clone(), None)),
+        (
+            "copyright_since",
+            from_file(config.copyright_since.map(|year| year.to_string()), None),
+        ),
+        (
+            "copyright_notice",
+            from_file(
+                config
+                    .copyright_notice
+                    .as_ref()
+                    .map(|notice| notice.replace('\n', "\\n")),
+                None,
+            ),
+        ),
+        ("guard_prefix", from_file(config.guard_prefix.clone(), None)),
+        (
+            "guard_path",
+            from_file(
+                config.guard_path.map(|path| path.to_string()),
+                Some(String::from("false")),
+            ),
+        ),
+        (
+            "guard_trailing_underscore",
+            from_file(
+                config
+                    .guard_trailing_underscore
+                    .map(|underscore| underscore.to_string()),
+                Some(String::from("false")),
+            ),
+        ),
+        (
+            "cpp_std",
+            from_file(config.cpp_std.as_ref().map(value_name), None),
+        ),
+        ("namespace", from_file(config.namespace.clone(), None)),
+        (
+            "date_format",
+            from_file(config.date_format.clone(), Some(String::from("us"))),
+        ),
+        (
+            "timezone",
+            from_file(
+                config.timezone.as_ref().map(value_name),
+                Some(value_name(&date::Timezone::default())),
+            ),
+        ),
+        (
+            "set_flags",
+            from_file(
+                config.set_flags.clone(),
+                Some(String::from(script::SET_FLAGS)),
+            ),
+        ),
+        (
+            "modeline",
+            from_file(config.modeline.as_ref().map(value_name), None),
+        ),
+        (
+            "editorconfig",
+            from_file(
+                config
+                    .editorconfig
+                    .map(|editorconfig| editorconfig.to_string()),
+                Some(String::from("true")),
+            ),
+        ),
+        (
+            "line_endings",
+            from_file(
+                config.line_endings.as_ref().map(value_name),
+                Some(value_name(&encoding::LineEnding::default())),
+            ),
+        ),
+        (
+            "bom",
+            from_file(
+                config.bom.map(|bom| bom.to_string()),
+                Some(String::from("false")),
+            ),
+        ),
+        (
+            "auto_edit",
+            from_file(
+                config.auto_edit.map(|edit| edit.to_string()),
+                Some(String::from("false")),
+            ),
+        ),
+        (
+            "template_dir",
+            from_file(
+                config
+                    .template_dir
+                    .as_ref()
+                    .map(|dir| dir.display().to_string()),
+                template::default_template_dir().map(|dir| dir.display().to_string()),
+            ),
+        ),
+    ];
 
-",
-        info.author, info.file, info.date,
-    ))
+    let width = settings.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, (value, source)) in settings {
+        let source = config.env_source(key).unwrap_or_else(|| source.to_string());
+        writeln!(
+            out,
+            "  {} = {value} ({source})",
+            style::key(&format!("{key:<width$}"))
+        )?;
+    }
+    let map_source = |key: &str| {
+        config
+            .env_source(key)
+            .unwrap_or_else(|| String::from("config file"))
+    };
+    for (filetype, shebang) in &config.shebang {
+        writeln!(
+            out,
+            "  {} = {shebang} ({})",
+            style::key(&format!("{:<width$}", format!("shebang {filetype}"))),
+            map_source("shebang"),
+        )?;
+    }
+    for (filetype, mode) in &config.permissions {
+        writeln!(
+            out,
+            "  {} = {mode} ({})",
+            style::key(&format!("{:<width$}", format!("permissions {filetype}"))),
+            map_source("permissions"),
+        )?;
+    }
+    for (filetype, eol) in &config.filetype_line_endings {
+        writeln!(
+            out,
+            "  {} = {} ({})",
+            style::key(&format!("{:<width$}", format!("line_endings {filetype}"))),
+            value_name(eol),
+            map_source("filetype_line_endings")
+        )?;
+    }
+    for (filetype, kind) in &config.default_kinds {
+        writeln!(
+            out,
+            "  {} = {} ({})",
+            style::key(&format!("{:<width$}", format!("default_kind {filetype}"))),
+            value_name(kind),
+            map_source("default_kinds")
+        )?;
+    }
+    for (extension, target) in &config.aliases {
+        writeln!(
+            out,
+            "  {} = {target} ({})",
+            style::key(&format!("{:<width$}", format!("alias .{extension}"))),
+            map_source("aliases")
+        )?;
+    }
+    Ok(())
+}
+
+fn run_template_command(command: TemplateCommand) -> Result<(), TfError> {
+    match command {
+        TemplateCommand::Add { url, name } => {
+            let path = shared::add(&url, name.as_deref())?;
+            println!("{} {}", style::done("ADDED"), path.display());
+        }
+        TemplateCommand::Update { name } => {
+            for repo in shared::update(name.as_deref())? {
+                println!("{} {repo}", style::done("UPDATED"));
+            }
+        }
+        TemplateCommand::List => {
+            for (name, path) in shared::repos() {
+                let url = shared::url(&path).unwrap_or_else(|| String::from("-"));
+                println!("{} {url} ({})", style::key(&name), path.display());
+            }
+        }
+        TemplateCommand::Remove { name } => {
+            let path = shared::remove(&name)?;
+            println!("{} {}", style::done("REMOVED"), path.display());
+        }
+    }
+    Ok(())
 }
 
-fn create_hpp_file(info: &Info) -> String {
-    String::from(format!(
-        "////////////////////////////////////////////////////////////////////////
-// Author  : {}
-// File    : {}
-// Date    : {}
-// Purpose : TODO
-////////////////////////////////////////////////////////////////////////
+fn run_generate_man_command(out_dir: Option<PathBuf>) -> Result<(), TfError> {
+    let mut command = cli().disable_help_subcommand(true);
+    command.build();
+    let Some(dir) = out_dir else {
+        print_out(io::stdout().lock().write_all(&man_page(&command)?))?;
+        return Ok(());
+    };
 
-#pragma once
+    fs::create_dir_all(&dir).map_err(|e| TfError::from(e).context(dir.display()))?;
+    let subcommands = command.get_subcommands().filter(|sub| !sub.is_hide_set());
+    for page in std::iter::once(&command).chain(subcommands) {
+        let path = dir.join(clap_mangen::Man::new(page.clone()).get_filename());
+        fs::write(&path, man_page(page)?).map_err(|e| TfError::from(e).context(path.display()))?;
+        println!("{} {}", style::done("CREATED"), path.display());
+    }
+    Ok(())
+}
 
-// STRUCTS
+/// The man page of `command`, with its examples in an EXAMPLES section
+/// rather than flowed together like other text after the help.
+fn man_page(command: &clap::Command) -> io::Result<Vec<u8>> {
+    let examples = command
+        .get_after_help()
+        .map(|examples| examples.to_string());
+    let mut page = Vec::new();
+    clap_mangen::Man::new(command.clone().after_help(None::<&str>)).render(&mut page)?;
 
-// FUNCTIONS
+    if let Some(examples) = examples {
+        let mut roff = roff::Roff::new();
+        roff.control("SH", ["EXAMPLES"]).control("nf", []);
+        let lines = examples.strip_prefix("Examples:").unwrap_or(&examples);
+        for line in lines.trim_matches('\n').lines() {
+            roff.text([roff::roman(line.strip_prefix("  ").unwrap_or(line))]);
+        }
+        roff.control("fi", []);
+        roff.to_writer(&mut page)?;
+    }
+    Ok(page)
+}
 
-////////////////////////////////////////////////////////////////////////
-",
-        info.author, info.file, info.date,
-    ))
+fn run_undo_command(dry_run: bool) -> Result<(), TfError> {
+    let cwd = env::current_dir()?;
+    let shown = |path: &Path| {
+        path.strip_prefix(&cwd)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    for outcome in undo::undo(dry_run)? {
+        match outcome {
+            undo::Outcome::Removed(path) if dry_run => {
+                println!("{} {}", style::planned("WOULD REMOVE"), shown(&path))
+            }
+            undo::Outcome::Removed(path) => println!("{} {}", style::done("REMOVED"), shown(&path)),
+            undo::Outcome::Restored(path) if dry_run => {
+                println!("{} {}", style::planned("WOULD RESTORE"), shown(&path))
+            }
+            undo::Outcome::Restored(path) => {
+                println!("{} {}", style::done("RESTORED"), shown(&path))
+            }
+            undo::Outcome::Missing(path) => println!(
+                "{} {} (already deleted)",
+                style::notice("SKIPPED"),
+                shown(&path)
+            ),
+        }
+    }
+    Ok(())
 }
 
-fn create_bash_file(info: &Info) -> String {
-    String::from(format!(
-        "#!/bin/bash
-########################################################################
-# Author  : {}
-# File    : {}
-# Date    : {}
-# Purpose : TODO
-########################################################################
-set -e # exit immediately on error
-set -u # treat unbound variables as errors
-set -x # enable tracing
-
-echo \"Hello, World!\"
-",
-        info.author, info.file, info.date,
-    ))
+fn run_config_command(command: ConfigCommand) -> Result<(), TfError> {
+    match command {
+        ConfigCommand::Init { force } => {
+            let path = config::init(force)?;
+            println!("{} {}", style::done("CREATED"), path.display());
+        }
+        ConfigCommand::Set { key, value } => {
+            let path = config::set(&key, &value)?;
+            println!("{} {key} in {}", style::done("SET"), path.display());
+        }
+        ConfigCommand::Show => print_out(show_config(&config::load()?, &mut io::stdout().lock()))?,
+    }
+    Ok(())
 }
 
-fn create_sv_file(info: &Info) -> String {
-    let module_name: Vec<&str> = info.file.split(".").collect();
+/// The command line, with examples in its help.
+fn cli() -> clap::Command {
+    help::with_examples(Args::command())
+}
+
+fn main() {
+    let args = Args::from_arg_matches(&cli().get_matches()).unwrap_or_else(|e| e.exit());
+    style::init(args.color);
+
+    let command = match args.command {
+        Some(command) => command,
+        None if args.supported_filetypes => Commands::List {
+            preview: None,
+            template_dir: args.new.header.template_dir,
+        },
+        None => Commands::New(Box::new(args.new)),
+    };
+    let result = match command {
+        Commands::New(args) => run_new_command(*args),
+        Commands::Config(command) => run_config_command(command),
+        Commands::List {
+            preview,
+            template_dir,
+        } => run_list_command(preview, template_dir),
+        Commands::Template(command) => run_template_command(command),
+        Commands::Annotate {
+            files,
+            dry_run,
+            header,
+        } => run_annotate_command(&files, dry_run, &header),
+        Commands::Update {
+            files,
+            dry_run,
+            header,
+        } => run_update_command(&files, dry_run, &header),
+        Commands::Undo { dry_run } => run_undo_command(dry_run),
+        Commands::Completions { shell } => {
+            let mut script = Vec::new();
+            clap_complete::generate(shell, &mut cli(), "tf", &mut script);
+            print_out(io::stdout().lock().write_all(&script))
+        }
+        Commands::GenerateMan { out_dir } => run_generate_man_command(out_dir),
+    };
+    if let Err(e) = result {
+        if !matches!(e, TfError::Reported(_)) {
+            eprintln!("{} {e}", style::error("ERROR"));
+        }
+        process::exit(e.exit_code());
+    }
+}
 
-    String::from(format!(
-        "////////////////////////////////////////////////////////////////////////
-// Author  : {}
-// File    : {}
-// Date    : {}
-// Purpose : TODO
-////////////////////////////////////////////////////////////////////////
+/// Generates `args.names` (or a manifest, or the file picked from prompts).
+/// Failures are shown as they happen; if there were any, the result is
+/// [`TfError::Reported`] with the exit code of the first.
+fn run_new_command(mut args: NewArgs) -> Result<(), TfError> {
+    let unnamed = args.names.is_empty() && args.from_manifest.is_none();
+    let prompting = args.interactive || (unnamed && interactive::available());
+    if args.interactive && !interactive::available() {
+        eprintln!(
+            "{}: Interactive mode needs a terminal.",
+            style::error("ERROR")
+        );
+        return Err(TfError::Reported(2));
+    }
+    if unnamed && !prompting {
+        eprintln!(
+            "{}: Program requires argument. See help with 'tf --help'",
+            style::error("ERROR")
+        );
+        return Err(TfError::Reported(2));
+    }
 
-`default_nettype none
+    let json = args.output == OutputFormat::Json;
+    let mut report = Report::default();
 
-module {} (
-  input logic clk,
-  input logic rst
-  );
+    // With `--output json`, failing before any file still prints a report.
+    let fail = |e: TfError, report: &mut Report| {
+        if !json {
+            return e;
+        }
+        report.error(None, &e);
+        report.print_json();
+        TfError::Reported(e.exit_code())
+    };
 
-  // TODO - Implementation
+    let config = config::load().map_err(|e| fail(e.context("reading config"), &mut report))?;
+    let template_dirs = resolve_template_dirs(args.header.template_dir.as_ref(), &config);
+    let registry = Registry::load(&template_dirs, &config.aliases)
+        .map_err(|e| fail(TfError::Usage(e), &mut report))?;
 
-endmodule
+    if prompting {
+        match interactive::prompt(&registry) {
+            Ok(Some(selection)) => {
+                args.names = vec![selection.name];
+                args.kind = selection.kind;
+                if let Some(purpose) = selection.purpose {
+                    args.header.vars.push((String::from("purpose"), purpose));
+                }
+            }
+            Ok(None) => return Ok(()),
+            Err(e) => return Err(TfError::Io(e)),
+        }
+    }
 
-`default_nettype wire
+    let mut created = 0;
+    let mut failed = 0;
+    let mut to_edit = Vec::new();
+    // Only files that didn't exist before are deleted by `tf undo`.
+    let mut new_files = Vec::new();
 
-",
-        info.author, info.file, info.date, module_name[0]
-    ))
-}
+    let mut record = |files: Vec<Planned>, multiple: bool, report: &mut Report| {
+        if args.stdout {
+            return;
+        }
+        for file in &files {
+            if json {
+                report.files.push(FileReport::new(
+                    &file.path,
+                    file.filetype,
+                    file.mode,
+                    args.dry_run,
+                ));
+            } else {
+                report_success(file, &args, multiple);
+            }
+        }
+        created += files.len();
+        to_edit.extend(files.into_iter().map(|file| file.path));
+    };
+    let mut exit_code = None;
+    let mut record_error = |name: &str, e: TfError, report: &mut Report| {
+        failed += 1;
+        exit_code.get_or_insert(e.exit_code());
+        if json {
+            report.error(Some(name), e);
+        } else {
+            eprintln!("{} {name}: {e}", style::error("ERROR"));
+        }
+    };
 
-fn create_svh_file(info: &Info) -> String {
-    let package_name: Vec<&str> = info.file.split(".").collect();
-    let package_name_no_file_ext = package_name[0];
-    let header_guard = package_name_no_file_ext.to_uppercase();
+    if let Some(manifest) = &args.from_manifest {
+        match generate_manifest(manifest, &args, &config, &registry) {
+            Ok((files, new)) => {
+                new_files.extend(new);
+                record(files, true, &mut report);
+            }
+            Err(errors) => {
+                for (name, e) in errors {
+                    record_error(&name, e, &mut report);
+                }
+            }
+        }
+    }
 
-    String::from(format!(
-        "////////////////////////////////////////////////////////////////////////
-// Author  : {}
-// File    : {}
-// Date    : {}
-// Purpose : TODO
-////////////////////////////////////////////////////////////////////////
+    // Render every name at once, then write them in order, so the output and
+    // what `--keep-going` skips are the same as one at a time.
+    let plans = parallel::map(&args.names, |name| {
+        let mut warnings = Vec::new();
+        let planned = plan(name, None, &args, &config, &registry, &mut warnings);
+        (warnings, planned)
+    });
+    let mut written: Vec<PathBuf> = Vec::new();
+    for (index, (name, (warnings, planned))) in args.names.iter().zip(plans).enumerate() {
+        print_warnings(&warnings);
+        let writing = !args.stdout && !args.dry_run;
+        let generated = planned.and_then(|files| {
+            // Planned side by side, an earlier name can't have existed yet.
+            if let Some(file) = files
+                .iter()
+                .find(|file| writing && !args.force && written.contains(&file.path))
+            {
+                return Err(exists_error(&file.path, &args));
+            }
+            new_files.extend(write(&files, &args)?);
+            if writing {
+                written.extend(files.iter().map(|file| file.path.clone()));
+            }
+            Ok(files)
+        });
+        match generated {
+            Ok(files) => {
+                let multiple = args.names.len() > 1 || files.len() > 1;
+                record(files, multiple, &mut report);
+            }
+            Err(e) => {
+                record_error(name, e, &mut report);
+                if !args.keep_going {
+                    report.skipped = args.names[index + 1..].to_vec();
+                    break;
+                }
+            }
+        }
+    }
 
-`ifndef {}
-`define {}
+    let mut filelist_edit = None;
+    if let Some(path) = args.filelist.as_ref().filter(|_| !args.stdout) {
+        let before = fs::read_to_string(path).ok();
+        let updated = update_filelist(path, &to_edit, &args, &config, &registry);
+        if updated
+            .as_ref()
+            .is_ok_and(|entries| !entries.is_empty() && !args.dry_run)
+        {
+            filelist_edit = Some(undo::FilelistEdit {
+                path: path.clone(),
+                before,
+            });
+        }
+        match updated {
+            Ok(entries) if json => report.filelist = entries,
+            Ok(entries) => {
+                let verb = if args.dry_run {
+                    style::planned("WOULD ADD")
+                } else {
+                    style::done("ADDED")
+                };
+                for entry in entries {
+                    println!("{verb} {entry} to {}", path.display());
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                exit_code.get_or_insert(e.exit_code());
+                if json {
+                    report.error(None, e);
+                } else {
+                    eprintln!("{} {e}", style::error("ERROR"));
+                }
+            }
+        }
+    }
 
-package {};
+    // Runs that wrote nothing leave the previous run to undo.
+    if !args.stdout && !args.dry_run && (!to_edit.is_empty() || filelist_edit.is_some()) {
+        if let Err(e) = undo::record(&new_files, filelist_edit) {
+            eprintln!(
+                "{} remembering the files for 'tf undo': {e}",
+                style::warning("WARNING")
+            );
+        }
+    }
 
-  // TODO - Implementation
+    if args.verbose && !args.stdout && !json {
+        let verb = if args.dry_run {
+            "would be created"
+        } else {
+            "created"
+        };
+        println!("{created} file(s) {verb}, {failed} failed");
+    }
 
-endpackage: {}
+    let edit = args.edit || (config.auto_edit.unwrap_or(false) && !args.no_edit);
+    if edit && !args.stdout && !args.dry_run {
+        if let Err(e) = editor::open(&to_edit).map_err(TfError::Other) {
+            exit_code.get_or_insert(e.exit_code());
+            if json {
+                report.error(None, e);
+            } else {
+                eprintln!("{} {e}", style::error("ERROR"));
+            }
+        }
+    }
 
-`endif
+    if json {
+        report.print_json();
+    }
 
-",
-        info.author,
-        info.file,
-        info.date,
-        header_guard,
-        header_guard,
-        package_name_no_file_ext,
-        package_name_no_file_ext
-    ))
+    match exit_code {
+        Some(code) => Err(TfError::Reported(code)),
+        None => Ok(()),
+    }
 }
@@ -0,0 +1,58 @@
+//! Manifests for `tf --from-manifest`: a TOML list of files to generate
+//! together.
+//!
+//! ```toml
+//! [vars]                      # for every file
+//! block = "uart"
+//!
+//! [[file]]
+//! path = "rtl/uart.sv"
+//! vars = { purpose = "UART top level" }
+//!
+//! [[file]]
+//! path = "tb/uart_tb.sv"
+//! kind = "tb"
+//!
+//! [[file]]
+//! path = "docs/uart.txt"
+//! type = "md"                 # template name or extension
+//! ```
+
+use crate::{error::TfError, filetype::Kind};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Variables for every file. A file's own `vars` and `--var` win.
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+    #[serde(default, rename = "file")]
+    pub files: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    /// Where the file goes, relative to the current directory.
+    pub path: String,
+    /// Template name or extension, instead of detecting it from `path`.
+    #[serde(rename = "type")]
+    pub filetype: Option<String>,
+    pub kind: Option<Kind>,
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+pub fn load(path: &Path) -> Result<Manifest, TfError> {
+    let contents = fs::read_to_string(path)?;
+    let manifest: Manifest =
+        toml::from_str(&contents).map_err(|e| TfError::Usage(e.to_string()))?;
+    if manifest.files.is_empty() {
+        return Err(TfError::Usage(String::from(
+            "The manifest has no [[file]] entries.",
+        )));
+    }
+    Ok(manifest)
+}
@@ -0,0 +1,84 @@
+//! Vim and Emacs modelines appended to generated files, so editors indent
+//! them the way they were written.
+
+use crate::{
+    editorconfig::{Indent, IndentStyle},
+    filetype::FileType,
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Modeline {
+    /// `// vim: ts=4 sw=4 et`
+    Vim,
+    /// A `Local Variables:` block
+    Emacs,
+}
+
+impl Modeline {
+    /// The modeline for `filetype` indented with `indent`, commented out
+    /// line by line.
+    pub fn lines(self, filetype: &FileType, indent: Indent) -> Vec<String> {
+        let width = indent.width;
+        let tabs = indent.style == IndentStyle::Tab;
+        let lines = match self {
+            Modeline::Vim => {
+                let expand = if tabs { "noet" } else { "et" };
+                let options = format!("ts={width} sw={width} {expand}");
+                // Text after the options needs the `set ...:` form.
+                let bare = format!("vim: {options}");
+                if filetype.comment.single_line(&bare).ends_with(&bare) {
+                    vec![bare]
+                } else {
+                    vec![format!("vim: set {options}:")]
+                }
+            }
+            Modeline::Emacs => {
+                let mut lines = vec![
+                    String::from("Local Variables:"),
+                    format!("indent-tabs-mode: {}", if tabs { "t" } else { "nil" }),
+                    format!("tab-width: {width}"),
+                ];
+                if let Some(offset) = emacs_offset(filetype) {
+                    lines.push(format!("{offset}: {width}"));
+                }
+                lines.push(String::from("End:"));
+                lines
+            }
+        };
+        lines
+            .iter()
+            .map(|line| filetype.comment.single_line(line))
+            .collect()
+    }
+}
+
+/// The Emacs variable holding the indent width of `filetype`'s major mode.
+fn emacs_offset(filetype: &FileType) -> Option<&'static str> {
+    // Variants share the mode of their base, e.g. `sv_tb` with `sv`.
+    let base = filetype.name.split('_').next().unwrap_or_default();
+    match base {
+        "c" | "h" | "cpp" | "hpp" | "java" | "ld" => Some("c-basic-offset"),
+        "js" => Some("js-indent-level"),
+        "ts" => Some("typescript-indent-level"),
+        "py" => Some("python-indent-offset"),
+        "bash" => Some("sh-basic-offset"),
+        "tcl" | "sdc" | "xdc" => Some("tcl-indent-level"),
+        "lua" => Some("lua-indent-level"),
+        "rb" => Some("ruby-indent-level"),
+        "sv" | "svh" | "v" => Some("verilog-indent-level"),
+        "vhd" => Some("vhdl-basic-offset"),
+        "cmake" => Some("cmake-tab-width"),
+        "compose" | "workflow" | "yaml" => Some("yaml-indent-offset"),
+        "proto" => Some("c-basic-offset"),
+        "ps1" => Some("powershell-indent"),
+        _ => None,
+    }
+}
+
+/// `contents` with `lines` appended after a blank line.
+pub fn append(contents: &str, lines: &[String]) -> String {
+    format!("{}\n\n{}\n", contents.trim_end(), lines.join("\n"))
+}
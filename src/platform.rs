@@ -0,0 +1,47 @@
+//! Permission handling. Only Unix has mode bits; elsewhere scripts are
+//! runnable because of their extension, so there is nothing to set.
+
+use std::{fs, io, path::Path};
+
+/// Applies `mode` to a freshly written file.
+#[cfg(unix)]
+pub fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut perms = fs::metadata(path)?.permissions();
+    perms.set_mode(mode);
+    fs::set_permissions(path, perms)
+}
+
+#[cfg(not(unix))]
+pub fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+/// Permissions a file of a type with `mode` will be created with, for
+/// `--dry-run`.
+pub fn planned_permissions(mode: Option<u32>) -> String {
+    match mode {
+        Some(mode) if cfg!(unix) => format!("{mode:04o}"),
+        _ => String::from("default"),
+    }
+}
+
+/// Permissions of an existing file, for `--verbose`.
+#[cfg(unix)]
+pub fn permissions(path: &Path) -> io::Result<String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)?.permissions().mode();
+    Ok(format!("{:04o}", mode & 0o777))
+}
+
+#[cfg(not(unix))]
+pub fn permissions(path: &Path) -> io::Result<String> {
+    let readonly = fs::metadata(path)?.permissions().readonly();
+    Ok(String::from(if readonly {
+        "read-only"
+    } else {
+        "read-write"
+    }))
+}
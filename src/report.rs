@@ -0,0 +1,94 @@
+//! The result of a run, printed as JSON by `--output json`.
+
+use crate::{filetype::FileType, platform};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::path::{self, Path};
+
+/// How the result of a run is printed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored messages for people
+    #[default]
+    Text,
+    /// A single JSON object on stdout, for editors and scripts
+    Json,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub files: Vec<FileReport>,
+    pub errors: Vec<ErrorReport>,
+    /// Names that weren't attempted because an earlier one failed.
+    pub skipped: Vec<String>,
+    /// Entries `--filelist` added to the filelist.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filelist: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub path: String,
+    pub absolute_path: String,
+    /// Name of the template the file was rendered from, e.g. `sv_tb`.
+    pub filetype: String,
+    pub description: &'static str,
+    pub status: Status,
+    /// `0744` style mode on Unix, `default` if the file wasn't written.
+    pub permissions: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Created,
+    WouldCreate,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    /// The name the error is about, if any.
+    pub name: Option<String>,
+    pub message: String,
+}
+
+impl FileReport {
+    pub fn new(path: &Path, filetype: &FileType, mode: Option<u32>, dry_run: bool) -> FileReport {
+        let (status, permissions) = if dry_run {
+            (Status::WouldCreate, platform::planned_permissions(mode))
+        } else {
+            (
+                Status::Created,
+                platform::permissions(path).unwrap_or_else(|_| String::from("unknown")),
+            )
+        };
+
+        FileReport {
+            path: path.display().to_string(),
+            absolute_path: path::absolute(path).map_or_else(
+                |_| path.display().to_string(),
+                |abs| abs.display().to_string(),
+            ),
+            filetype: filetype.name.to_string(),
+            description: filetype.description,
+            status,
+            permissions,
+        }
+    }
+}
+
+impl Report {
+    pub fn error(&mut self, name: Option<&str>, message: impl ToString) {
+        self.errors.push(ErrorReport {
+            name: name.map(String::from),
+            message: message.to_string(),
+        });
+    }
+
+    pub fn print_json(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => println!("{json}"),
+            Err(e) => eprintln!("serializing the report: {e}"),
+        }
+    }
+}
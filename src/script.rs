@@ -0,0 +1,59 @@
+//! Interpreter lines and shell options for script templates.
+
+use crate::{config::Config, filetype::FileType};
+use std::collections::BTreeMap;
+
+/// `set` flags of Bash scripts unless `--set-flags`/`set_flags` say
+/// otherwise.
+pub const SET_FLAGS: &str = "eux";
+
+/// Single-letter options of Bash's `set`. Anything else is ignored.
+const FLAGS: &str = "abefhkmnptuvxBCHP";
+
+/// Script settings after merging the command line with the config file.
+#[derive(Debug)]
+pub struct Options {
+    shebang: Option<String>,
+    /// The config's interpreters by filetype.
+    shebangs: BTreeMap<String, String>,
+    set_flags: String,
+}
+
+impl Options {
+    pub fn resolve(
+        shebang: Option<&String>,
+        set_flags: Option<&String>,
+        config: &Config,
+    ) -> Options {
+        Options {
+            shebang: shebang.cloned(),
+            shebangs: config.shebang.clone(),
+            set_flags: set_flags
+                .or(config.set_flags.as_ref())
+                .map_or(SET_FLAGS, String::as_str)
+                .to_string(),
+        }
+    }
+
+    /// The interpreter for `filetype` without `#!`, e.g. `/usr/bin/env
+    /// bash`. `None` leaves it to the template.
+    pub fn shebang(&self, filetype: &FileType) -> Option<String> {
+        self.shebang
+            .as_ref()
+            .or_else(|| {
+                filetype
+                    .config_keys()
+                    .find_map(|key| self.shebangs.get(key))
+            })
+            .map(|shebang| shebang.trim_start_matches("#!").trim().to_string())
+    }
+
+    /// One `set` flag per entry, e.g. `["e", "u"]` for `set -e`/`set -u`.
+    pub fn set_flags(&self) -> Vec<String> {
+        self.set_flags
+            .chars()
+            .filter(|flag| FLAGS.contains(*flag))
+            .map(String::from)
+            .collect()
+    }
+}
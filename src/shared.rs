@@ -0,0 +1,151 @@
+//! Shared template repositories: git repositories of `.tera` files cloned
+//! into the tf data directory and merged into the registry below the user's
+//! own template directory.
+
+use crate::error::TfError;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Where shared repositories are cloned: `$XDG_DATA_HOME/tf/repos`.
+pub fn root() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tf").join("repos"))
+}
+
+fn required_root() -> Result<PathBuf, TfError> {
+    root().ok_or_else(|| TfError::Other(String::from("Couldn't determine the data directory.")))
+}
+
+/// Names and clones of every shared repository, sorted by name.
+pub fn repos() -> Vec<(String, PathBuf)> {
+    let mut repos: Vec<(String, PathBuf)> = root()
+        .and_then(|root| fs::read_dir(root).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?.to_string();
+            path.is_dir().then_some((name, path))
+        })
+        .collect();
+    repos.sort();
+    repos
+}
+
+/// The directory templates are read from in a clone: its `templates/`
+/// subdirectory if it has one, otherwise the top level.
+pub fn template_dir(repo: &Path) -> PathBuf {
+    let nested = repo.join("templates");
+    if nested.is_dir() {
+        nested
+    } else {
+        repo.to_path_buf()
+    }
+}
+
+/// Name of the repository a template directory belongs to, if any.
+pub fn repo_name(dir: &Path) -> Option<String> {
+    let relative = dir.strip_prefix(root()?).ok()?;
+    let name = relative.components().next()?.as_os_str().to_str()?;
+    Some(name.to_string())
+}
+
+/// Default name for a repository cloned from `url`: its last path segment
+/// without `.git`.
+fn name_from_url(url: &str) -> Option<&str> {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()?
+        .trim_end_matches(".git");
+    (!name.is_empty()).then_some(name)
+}
+
+/// Clones `url` as the shared repository `name`, returning where it went.
+pub fn add(url: &str, name: Option<&str>) -> Result<PathBuf, TfError> {
+    let name = name.or_else(|| name_from_url(url)).ok_or_else(|| {
+        TfError::Usage(format!(
+            "Couldn't derive a name from '{url}'. Use '--name'."
+        ))
+    })?;
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(TfError::Usage(format!(
+            "'{name}' is not a valid repository name"
+        )));
+    }
+
+    let root = required_root()?;
+    let path = root.join(name);
+    if path.exists() {
+        return Err(TfError::Exists(format!(
+            "A template repository named '{name}' already exists. Use 'tf template update {name}' to refresh it."
+        )));
+    }
+
+    fs::create_dir_all(&root)?;
+    // `--` keeps a URL starting with `-` from being read as an option.
+    git(&root, &["clone", "--depth", "1", "--", url, name])?;
+    Ok(path)
+}
+
+/// Pulls the latest commits of the repository `name`, or of every one.
+/// Returns the names of the repositories that were updated.
+pub fn update(name: Option<&str>) -> Result<Vec<String>, TfError> {
+    let repos: Vec<(String, PathBuf)> = repos()
+        .into_iter()
+        .filter(|(repo, _)| name.is_none_or(|name| name == repo))
+        .collect();
+    if let (Some(name), true) = (name, repos.is_empty()) {
+        return Err(TfError::Usage(format!(
+            "No template repository named '{name}'"
+        )));
+    }
+
+    for (_, path) in &repos {
+        git(path, &["pull", "--ff-only"])?;
+    }
+    Ok(repos.into_iter().map(|(name, _)| name).collect())
+}
+
+/// Deletes the clone of the repository `name`.
+pub fn remove(name: &str) -> Result<PathBuf, TfError> {
+    let (_, path) = repos()
+        .into_iter()
+        .find(|(repo, _)| repo == name)
+        .ok_or_else(|| TfError::Usage(format!("No template repository named '{name}'")))?;
+    fs::remove_dir_all(&path)?;
+    Ok(path)
+}
+
+/// URL the repository at `path` was cloned from.
+pub fn url(path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+    let url = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !url.is_empty()).then_some(url)
+}
+
+/// Runs git in `dir`, letting it print its own progress and errors.
+fn git(dir: &Path, args: &[&str]) -> Result<(), TfError> {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .map_err(|e| TfError::Other(format!("Couldn't run git: {e}")))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(TfError::Other(format!(
+            "'git {}' failed ({status})",
+            args.join(" ")
+        )))
+    }
+}
@@ -0,0 +1,117 @@
+//! Colors for everything tf prints. Every styled string goes through here,
+//! so `--color`, `NO_COLOR` and redirected output are handled in one place.
+
+use clap::ValueEnum;
+use colored::{ColoredString, Colorize};
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::OnceLock,
+};
+
+/// When to color output.
+#[derive(Debug, Default, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color terminals unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Whether stdout and stderr are colored.
+static ENABLED: OnceLock<(bool, bool)> = OnceLock::new();
+
+/// Decides once whether stdout and stderr get colors. With `auto`, a stream
+/// is colored if it's a terminal, `NO_COLOR` is unset and `CLICOLOR` isn't
+/// `0`; `CLICOLOR_FORCE` colors it anyway.
+pub fn init(choice: ColorChoice) {
+    let _ = ENABLED.set(decide(choice));
+    // tf decides per stream, so `colored` must never second-guess it.
+    colored::control::set_override(true);
+}
+
+fn decide(choice: ColorChoice) -> (bool, bool) {
+    let set = |key: &str| env::var(key).is_ok_and(|value| !value.is_empty());
+    let auto = |terminal: bool| {
+        if set("NO_COLOR") {
+            false
+        } else if set("CLICOLOR_FORCE") && env::var("CLICOLOR_FORCE").as_deref() != Ok("0") {
+            true
+        } else {
+            terminal && env::var("CLICOLOR").as_deref() != Ok("0")
+        }
+    };
+
+    match choice {
+        ColorChoice::Always => (true, true),
+        ColorChoice::Never => (false, false),
+        ColorChoice::Auto => (
+            auto(io::stdout().is_terminal()),
+            auto(io::stderr().is_terminal()),
+        ),
+    }
+}
+
+fn enabled() -> (bool, bool) {
+    *ENABLED.get_or_init(|| decide(ColorChoice::Auto))
+}
+
+/// Whether prompts and messages on stderr are colored.
+pub fn stderr_colored() -> bool {
+    enabled().1
+}
+
+fn stdout(text: &str, style: fn(&str) -> ColoredString) -> String {
+    if enabled().0 {
+        style(text).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+fn stderr(text: &str, style: fn(&str) -> ColoredString) -> String {
+    if stderr_colored() {
+        style(text).to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// `ERROR` and friends on stderr.
+pub fn error(text: &str) -> String {
+    stderr(text, |text| text.red())
+}
+
+pub fn warning(text: &str) -> String {
+    stderr(text, |text| text.yellow())
+}
+
+/// Labels of things that were done, e.g. `CREATED`.
+pub fn done(text: &str) -> String {
+    stdout(text, |text| text.green())
+}
+
+/// Labels of things a dry run would do, e.g. `WOULD CREATE`.
+pub fn planned(text: &str) -> String {
+    stdout(text, |text| text.cyan())
+}
+
+/// Things that were left alone or deserve a second look.
+pub fn notice(text: &str) -> String {
+    stdout(text, |text| text.yellow())
+}
+
+pub fn heading(text: &str) -> String {
+    stdout(text, |text| text.bright_cyan().bold().underline())
+}
+
+/// Names in listings, e.g. a filetype or config key.
+pub fn key(text: &str) -> String {
+    stdout(text, |text| text.bright_cyan().bold())
+}
+
+/// Values in listings, e.g. how a filetype is selected.
+pub fn value(text: &str) -> String {
+    stdout(text, |text| text.bright_green().bold())
+}
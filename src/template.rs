@@ -0,0 +1,265 @@
+use crate::{cpp::Namespace, error::TfError, filetype::BUILTINS, guard::GuardStyle};
+use serde::Serialize;
+use std::{collections::BTreeMap, error::Error, fs, path::PathBuf};
+use tera::{Context, Tera};
+
+/// Variables available to every template, built-in or user-provided.
+#[derive(Debug, Serialize)]
+pub struct Info {
+    pub date: String,
+    /// ISO 8601 timestamp with the UTC offset.
+    pub datetime: String,
+    pub author: String,
+    pub file: String,
+    pub stem: String,
+    /// The stem as a legal identifier, e.g. `my_uart` for `my-uart.sv`.
+    pub ident: String,
+    /// Name of the directory the file is created in.
+    pub dir: String,
+    /// Include guard macro, e.g. `UART_H`.
+    pub guard: String,
+    /// `ifndef` or `pragma-once`. Unset leaves it to the template.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guard_style: Option<GuardStyle>,
+    /// Interpreter for the `#!` line, e.g. `/usr/bin/env bash`. Unset
+    /// leaves it to the template.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shebang: Option<String>,
+    /// `set` flags of shell scripts, one letter each.
+    pub set_flags: Vec<String>,
+    /// C++ standard, e.g. `20`. Unset leaves it to the template.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpp_std: Option<u16>,
+    /// Namespace C++ sources and headers are wrapped in, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<Namespace>,
+    /// PascalCase form of the stem, e.g. `UartDriver` for `uart_driver.py`.
+    pub class_name: String,
+    pub comment: CommentStyle,
+    /// Module instantiated by a testbench, e.g. `uart` for `uart_tb.sv`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dut: Option<String>,
+    /// `Copyright (c) 2019-2025 Acme Corp.`, if a holder is configured.
+    pub copyright: Option<String>,
+    /// Lines under the copyright line, e.g. `All rights reserved.`.
+    pub copyright_notice: Vec<String>,
+    /// SPDX identifier for the header, if any.
+    pub license: Option<String>,
+    /// License notice placed under the SPDX identifier, one entry per line.
+    pub license_notice: Vec<String>,
+    /// User-provided `--var` values. Built-in variables take precedence.
+    #[serde(skip)]
+    pub vars: BTreeMap<String, String>,
+}
+
+/// How the header banner is commented out, exposed to templates as
+/// `comment.open`, `comment.line` (prefix of every line) and `comment.close`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CommentStyle {
+    pub open: &'static str,
+    pub line: &'static str,
+    pub close: &'static str,
+}
+
+impl CommentStyle {
+    pub const SLASHES: CommentStyle = CommentStyle {
+        open: "////////////////////////////////////////////////////////////////////////",
+        line: "// ",
+        close: "////////////////////////////////////////////////////////////////////////",
+    };
+
+    pub const HASHES: CommentStyle = CommentStyle {
+        open: "########################################################################",
+        line: "# ",
+        close: "########################################################################",
+    };
+
+    pub const DASHES: CommentStyle = CommentStyle {
+        open: "------------------------------------------------------------------------",
+        line: "-- ",
+        close: "------------------------------------------------------------------------",
+    };
+
+    pub const REM: CommentStyle = CommentStyle {
+        open: "REM ====================================================================",
+        line: "REM ",
+        close: "REM ====================================================================",
+    };
+
+    pub const PERCENT: CommentStyle = CommentStyle {
+        open: "%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%",
+        line: "% ",
+        close: "%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%%",
+    };
+
+    pub const HTML: CommentStyle = CommentStyle {
+        open: "<!--",
+        line: "",
+        close: "-->",
+    };
+
+    pub const BLOCK: CommentStyle = CommentStyle {
+        open: "/***********************************************************************",
+        line: " * ",
+        close: " **********************************************************************/",
+    };
+
+    pub const DOCSTRING: CommentStyle = CommentStyle {
+        open: "\"\"\"",
+        line: "",
+        close: "\"\"\"",
+    };
+
+    /// For formats without comments, such as JSON. Nothing is added to
+    /// their files but what the template writes.
+    pub const NONE: CommentStyle = CommentStyle {
+        open: "",
+        line: "",
+        close: "",
+    };
+
+    pub fn allows_comments(&self) -> bool {
+        !(self.open.is_empty() && self.line.is_empty() && self.close.is_empty())
+    }
+
+    /// `text` commented out on a line of its own, outside the banner, e.g.
+    /// `// text` or `/* text */`.
+    pub fn single_line(&self, text: &str) -> String {
+        match self.line {
+            " * " => format!("/* {text} */"),
+            "" if self.open == "<!--" => format!("<!-- {text} -->"),
+            // Docstrings are Python's; its comments start with `#`.
+            "" => format!("# {text}"),
+            line => format!("{line}{text}"),
+        }
+    }
+}
+
+/// Templates included by others rather than rendered for a file. Like the
+/// filetype templates, each can be overridden from the template dir.
+const PARTIALS: &[(&str, &str)] = &[("banner", include_str!("../templates/banner.tera"))];
+
+/// Directory of user partials in a template dir, included as
+/// `partials/<name>`.
+const PARTIALS_DIR: &str = "partials";
+
+pub fn is_partial(name: &str) -> bool {
+    PARTIALS.iter().any(|(partial, _)| *partial == name)
+}
+
+/// Default location of user templates: `$XDG_CONFIG_HOME/tf/templates`.
+pub fn default_template_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("tf").join("templates"))
+}
+
+/// The first `<dir>/<name>.tera` in `template_dirs`, if any.
+pub fn find_template(name: &str, template_dirs: &[PathBuf]) -> Option<PathBuf> {
+    template_dirs
+        .iter()
+        .map(|dir| dir.join(format!("{name}.tera")))
+        .find(|path| path.is_file())
+}
+
+/// Loads the template called `name`, preferring `<dir>/<name>.tera` from
+/// the first of `template_dirs` that has one over the built-in one.
+fn load_template(name: &str, template_dirs: &[PathBuf]) -> Result<String, TfError> {
+    if let Some(user_template) = find_template(name, template_dirs) {
+        return fs::read_to_string(&user_template)
+            .map_err(|e| TfError::Io(format!("reading '{}': {e}", user_template.display())));
+    }
+
+    PARTIALS
+        .iter()
+        .copied()
+        .chain(
+            BUILTINS
+                .iter()
+                .map(|filetype| (filetype.name.as_ref(), filetype.source)),
+        )
+        .find(|(builtin, _)| *builtin == name)
+        .map(|(_, source)| source.to_string())
+        .ok_or_else(|| TfError::Unsupported(format!("No template named '{name}'")))
+}
+
+/// Renders the template called `name` with `info` as its context. The
+/// `banner` partial and the user's `partials/` are available to
+/// `{% include %}`, `{% extends %}` and `{% import %}`.
+pub fn render(name: &str, info: &Info, template_dirs: &[PathBuf]) -> Result<String, TfError> {
+    let source = load_template(name, template_dirs)?;
+    let tera = engine(template_dirs, Some((name, source)))?;
+
+    tera.render(name, &context(info)?)
+        .map_err(|e| TfError::Other(error_chain(&e)))
+}
+
+/// A Tera instance holding the banner, the user's partials and `template`.
+fn engine(template_dirs: &[PathBuf], template: Option<(&str, String)>) -> Result<Tera, TfError> {
+    let banner = load_template("banner", template_dirs)?;
+    let mut templates = load_partials(template_dirs)?;
+    templates.insert(String::from("banner"), banner);
+    if let Some((name, source)) = template {
+        templates.insert(name.to_string(), source);
+    }
+
+    let mut tera = Tera::default();
+    tera.autoescape_on(vec![]);
+    tera.add_raw_templates(templates)
+        .map_err(|e| TfError::Other(error_chain(&e)))?;
+    Ok(tera)
+}
+
+/// Every `<dir>/partials/<name>.tera` in `template_dirs`, named
+/// `partials/<name>`. A partial in an earlier dir hides one of the same name
+/// in later ones, so users can override a shared repository's partials.
+fn load_partials(template_dirs: &[PathBuf]) -> Result<BTreeMap<String, String>, TfError> {
+    let mut partials = BTreeMap::new();
+    for dir in template_dirs.iter().rev() {
+        let Ok(entries) = fs::read_dir(dir.join(PARTIALS_DIR)) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let Some(stem) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .filter(|_| path.extension().is_some_and(|ext| ext == "tera"))
+            else {
+                continue;
+            };
+            let source = fs::read_to_string(&path)
+                .map_err(|e| TfError::Io(format!("reading '{}': {e}", path.display())))?;
+            partials.insert(format!("{PARTIALS_DIR}/{stem}"), source);
+        }
+    }
+    Ok(partials)
+}
+
+/// The template context for `info`. `--var` values never shadow built-ins.
+fn context(info: &Info) -> Result<Context, TfError> {
+    let mut context = Context::from_serialize(info).map_err(|e| TfError::Other(error_chain(&e)))?;
+    for (key, value) in &info.vars {
+        if !context.contains_key(key) {
+            context.insert(key, value);
+        }
+    }
+    Ok(context)
+}
+
+/// Renders only the header banner, for adding it to an existing file.
+pub fn render_banner(info: &Info, template_dirs: &[PathBuf]) -> Result<String, TfError> {
+    engine(template_dirs, None)?
+        .render("banner", &context(info)?)
+        .map_err(|e| TfError::Other(error_chain(&e)))
+}
+
+/// Tera nests the useful part of its errors in `source()`; flatten the chain
+/// so the user sees why a template failed, not just that it did.
+fn error_chain(err: &tera::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
@@ -0,0 +1,179 @@
+//! The files written by the last run, remembered so `tf undo` can delete
+//! them again and take their entries out of the filelist.
+
+use crate::error::TfError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    error::Error,
+    fs, io,
+    path::{self, Path, PathBuf},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LastRun {
+    files: Vec<Written>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filelist: Option<EditedFilelist>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Written {
+    /// Absolute path of the file.
+    path: PathBuf,
+    /// SHA-256 of the contents tf wrote, to notice later edits.
+    sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EditedFilelist {
+    /// Absolute path of the filelist.
+    path: PathBuf,
+    /// Its contents before the run, unless tf created it.
+    before: Option<String>,
+    /// SHA-256 of its contents after the run.
+    sha256: String,
+}
+
+/// A filelist `tf new --filelist` added entries to.
+pub struct FilelistEdit {
+    pub path: PathBuf,
+    /// Its contents before, `None` if it was created.
+    pub before: Option<String>,
+}
+
+/// What `tf undo` did, or would do, with a file.
+pub enum Outcome {
+    Removed(PathBuf),
+    /// The file was already deleted.
+    Missing(PathBuf),
+    /// The filelist got back the contents it had before.
+    Restored(PathBuf),
+}
+
+/// Where the last run is remembered: `$XDG_DATA_HOME/tf/last-run.json`.
+fn state_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("tf").join("last-run.json"))
+}
+
+fn required_state_path() -> Result<PathBuf, TfError> {
+    state_path()
+        .ok_or_else(|| TfError::Other(String::from("Couldn't determine the data directory.")))
+}
+
+fn sha256(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Remembers `paths`, the files the last run created, and the filelist it
+/// edited, replacing the previous run. Files it only overwrote are left
+/// out, so `tf undo` never deletes them.
+pub fn record(paths: &[PathBuf], filelist: Option<FilelistEdit>) -> Result<(), Box<dyn Error>> {
+    let files = paths
+        .iter()
+        .map(|path| {
+            Ok(Written {
+                path: path::absolute(path)?,
+                sha256: sha256(&fs::read(path)?),
+            })
+        })
+        .collect::<io::Result<_>>()?;
+    let filelist = filelist
+        .map(|edit| {
+            Ok::<_, io::Error>(EditedFilelist {
+                sha256: sha256(&fs::read(&edit.path)?),
+                path: path::absolute(&edit.path)?,
+                before: edit.before,
+            })
+        })
+        .transpose()?;
+    let state = required_state_path()?;
+    if let Some(parent) = state.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        state,
+        serde_json::to_string_pretty(&LastRun { files, filelist })?,
+    )?;
+    Ok(())
+}
+
+/// Deletes the files of the last run and restores the filelist it edited,
+/// or does nothing if any of them was changed since. With `dry_run` nothing
+/// is deleted.
+pub fn undo(dry_run: bool) -> Result<Vec<Outcome>, TfError> {
+    let state = required_state_path()?;
+    let last_run: LastRun = match fs::read_to_string(&state) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| TfError::Other(format!("{}: {e}", state.display())))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(TfError::Other(String::from("Nothing to undo.")));
+        }
+        Err(e) => return Err(TfError::from(e).context(state.display())),
+    };
+    if last_run.files.is_empty() && last_run.filelist.is_none() {
+        return Err(TfError::Other(String::from(
+            "Nothing to undo. The last run created no files.",
+        )));
+    }
+
+    let mut outcomes = Vec::new();
+    for file in &last_run.files {
+        let unchanged = check(&file.path, &file.sha256, "it was created")?;
+        outcomes.push(if unchanged {
+            Outcome::Removed(file.path.clone())
+        } else {
+            Outcome::Missing(file.path.clone())
+        });
+    }
+    if let Some(filelist) = &last_run.filelist {
+        if check(&filelist.path, &filelist.sha256, "entries were added to it")? {
+            outcomes.push(match filelist.before {
+                Some(_) => Outcome::Restored(filelist.path.clone()),
+                None => Outcome::Removed(filelist.path.clone()),
+            });
+        }
+    }
+    if dry_run {
+        return Ok(outcomes);
+    }
+
+    for outcome in &outcomes {
+        match outcome {
+            Outcome::Removed(path) => remove(path)?,
+            Outcome::Restored(path) => {
+                let before = last_run
+                    .filelist
+                    .as_ref()
+                    .and_then(|filelist| filelist.before.as_deref())
+                    .unwrap_or_default();
+                fs::write(path, before)
+                    .map_err(|e| TfError::Io(format!("restoring '{}': {e}", path.display())))?;
+            }
+            Outcome::Missing(_) => {}
+        }
+    }
+    fs::remove_file(&state)?;
+    Ok(outcomes)
+}
+
+/// Whether the file at `path` still has the contents tf wrote (`false` if
+/// it was deleted). Fails if it was changed since `when`.
+fn check(path: &Path, expected: &str, when: &str) -> Result<bool, TfError> {
+    match fs::read(path) {
+        Ok(contents) if sha256(&contents) == expected => Ok(true),
+        Ok(_) => Err(TfError::Other(format!(
+            "'{}' changed since {when}. Nothing was deleted.",
+            path.display()
+        ))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(TfError::Io(format!("reading '{}': {e}", path.display()))),
+    }
+}
+
+fn remove(path: &Path) -> Result<(), TfError> {
+    fs::remove_file(path).map_err(|e| TfError::Io(format!("deleting '{}': {e}", path.display())))
+}
@@ -0,0 +1,1182 @@
+//! Command line behavior: which files are written, and how failures are
+//! reported.
+
+mod common;
+
+use common::Sandbox;
+use std::fs;
+
+#[test]
+fn creates_the_file_with_a_header() {
+    let sandbox = Sandbox::new();
+
+    sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "uart.c"])
+        .assert()
+        .success();
+
+    insta::assert_snapshot!(sandbox.read("uart.c"));
+}
+
+#[test]
+fn new_is_the_default_command() {
+    let sandbox = Sandbox::new();
+
+    let new = sandbox
+        .tf()
+        .args(["new", "-a", "Jane Doe", "--stdout", "uart.c"])
+        .output()
+        .unwrap();
+    let shortcut = sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "--stdout", "uart.c"])
+        .output()
+        .unwrap();
+
+    assert!(new.status.success());
+    assert_eq!(new.stdout, shortcut.stdout);
+}
+
+#[test]
+fn completions_are_printed_for_a_shell() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().args(["completions", "bash"]).output().unwrap();
+
+    assert!(output.status.success());
+    let script = String::from_utf8(output.stdout).unwrap();
+    assert!(script.contains("complete -F _tf"));
+}
+
+#[test]
+fn creates_missing_directories() {
+    let sandbox = Sandbox::new();
+
+    sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "rtl/core/alu.sv"])
+        .assert()
+        .success();
+
+    assert!(sandbox.path("rtl/core/alu.sv").is_file());
+}
+
+#[cfg(unix)]
+#[test]
+fn bash_scripts_are_executable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new();
+
+    sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "run.bash"])
+        .assert()
+        .success();
+
+    let mode = sandbox
+        .path("run.bash")
+        .metadata()
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o744);
+}
+
+#[test]
+fn class_skeletons_come_with_a_header() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "--class", "uart_driver.cpp"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    insta::assert_snapshot!(common::stdout(&output), @r"
+    CREATED uart_driver.cpp
+    CREATED uart_driver.hpp
+    ");
+    assert!(sandbox.path("uart_driver.hpp").is_file());
+}
+
+#[test]
+fn dry_run_writes_nothing() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "--dry-run", "uart.c", "run.bash"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    insta::assert_snapshot!(common::stdout(&output), @r"
+    WOULD CREATE uart.c (C, permissions: default)
+    WOULD CREATE run.bash (Bash, permissions: 0744)
+    ");
+    assert!(!sandbox.path("uart.c").exists());
+}
+
+#[test]
+fn list_shows_every_filetype() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().arg("list").output().unwrap();
+
+    assert!(output.status.success());
+    insta::assert_snapshot!(common::stdout(&output));
+}
+
+#[test]
+fn unsupported_extension_fails() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().arg("notes.xyz").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR notes.xyz: Filetype '.xyz' is not supported. Run 'tf list' for available filetypes.");
+}
+
+#[test]
+fn fill_writes_only_into_empty_files() {
+    let sandbox = Sandbox::new();
+    sandbox.write("uart.c", " \n");
+    sandbox.write("spi.c", "keep me\n");
+
+    let output = sandbox.tf().arg("uart.c").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR uart.c: 'uart.c' already exists but is empty. Use '--fill' to write into it.");
+
+    let output = sandbox
+        .tf()
+        .args(["--fill", "--keep-going", "uart.c", "spi.c"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR spi.c: 'spi.c' isn't empty. '--fill' only writes into empty files.");
+    assert!(sandbox.read("uart.c").contains("int main("));
+    assert_eq!(sandbox.read("spi.c"), "keep me\n");
+}
+
+#[test]
+fn misspelled_names_get_suggestions() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args(["--keep-going", "main.pyy", "Makefil"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    insta::assert_snapshot!(common::stderr(&output), @r"
+    ERROR main.pyy: Filetype '.pyy' is not supported. Did you mean '.py'? Run 'tf list' for available filetypes.
+    ERROR Makefil: Filename with file extension is expected. Did you mean 'Makefile' or 'makefile'?
+    ");
+}
+
+#[test]
+fn missing_extension_fails() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().arg("notes").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR notes: Filename with file extension is expected.");
+}
+
+#[test]
+fn bare_extensions_ask_for_a_filename() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args(["--keep-going", ".c", ".pyy"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    insta::assert_snapshot!(common::stderr(&output), @r"
+    ERROR .c: Filename is expected before the extension, e.g. 'name.c'.
+    ERROR .pyy: Filename with file extension is expected. Did you mean '.py'?
+    ");
+}
+
+#[test]
+fn every_command_exits_with_the_failure_code() {
+    let sandbox = Sandbox::new();
+    sandbox.write("notes.xyz", "notes\n");
+
+    let output = sandbox
+        .tf()
+        .args(["annotate", "notes.xyz"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(4));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR notes.xyz: Filetype '.xyz' is not supported. Run 'tf list' for available filetypes.");
+
+    let output = sandbox
+        .tf()
+        .args(["annotate", "missing.c"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(5));
+    assert!(common::stderr(&output).starts_with("ERROR missing.c: "));
+
+    let output = sandbox.tf().args(["config", "init"]).output().unwrap();
+    assert!(output.status.success());
+    let output = sandbox.tf().args(["config", "init"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(common::stderr(&output).contains("already exists. Use '--force' to overwrite it."));
+
+    let output = sandbox
+        .tf()
+        .args(["uart.c"])
+        .env("TF_LICENSE_STYLE", "sideways")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(common::stderr(&output).starts_with("ERROR reading config: $TF_LICENSE_STYLE: "));
+}
+
+#[test]
+fn missing_name_fails() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR: Program requires argument. See help with 'tf --help'");
+}
+
+#[test]
+fn module_and_class_names_can_differ_from_the_file() {
+    let sandbox = Sandbox::new();
+
+    let module = sandbox
+        .tf()
+        .args(["--stdout", "--module-name", "top", "top-wrapper.sv"])
+        .output()
+        .unwrap();
+    let class = sandbox
+        .tf()
+        .args([
+            "--stdout",
+            "--class",
+            "--class-name",
+            "Widget",
+            "widget_impl.py",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(common::stderr(&module).is_empty());
+    assert!(common::stdout(&module).contains("module top ("));
+    assert!(common::stdout(&class).contains("class Widget:"));
+}
+
+#[test]
+fn class_names_apply_to_java_and_unused_names_warn() {
+    let sandbox = Sandbox::new();
+
+    let java = sandbox
+        .tf()
+        .args(["--stdout", "--class-name", "Main", "app.java"])
+        .output()
+        .unwrap();
+    let unused = sandbox
+        .tf()
+        .args([
+            "--stdout",
+            "--class-name",
+            "Main",
+            "--module-name",
+            "top",
+            "run.bash",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(common::stdout(&java).contains("public class Main {"));
+    assert!(common::stderr(&java).is_empty());
+    insta::assert_snapshot!(common::stderr(&unused), @r"
+    WARNING run.bash: Bash files don't use '--module-name', ignoring it
+    WARNING run.bash: Bash files don't use '--class-name', ignoring it
+    ");
+}
+
+#[test]
+fn existing_files_need_force() {
+    let sandbox = Sandbox::new();
+    sandbox.write("uart.c", "keep me\n");
+
+    let output = sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "uart.c"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR uart.c: 'uart.c' already exists. Use '--force' to overwrite it.");
+    assert_eq!(sandbox.read("uart.c"), "keep me\n");
+
+    sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "--force", "uart.c"])
+        .assert()
+        .success();
+    assert_ne!(sandbox.read("uart.c"), "keep me\n");
+}
+
+#[test]
+fn stops_at_the_first_failure_unless_keep_going() {
+    let sandbox = Sandbox::new();
+
+    sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "bad.xyz", "a.c"])
+        .assert()
+        .code(4);
+    assert!(!sandbox.path("a.c").exists());
+
+    sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "--keep-going", "bad.xyz", "a.c"])
+        .assert()
+        .code(4);
+    assert!(sandbox.path("a.c").exists());
+}
+
+#[test]
+fn exit_code_comes_from_the_first_failure() {
+    let sandbox = Sandbox::new();
+    sandbox.write("uart.c", "keep me\n");
+
+    sandbox
+        .tf()
+        .args(["--keep-going", "uart.c", "bad.xyz"])
+        .assert()
+        .code(3);
+    sandbox
+        .tf()
+        .args(["--keep-going", "bad.xyz", "uart.c"])
+        .assert()
+        .code(4);
+    sandbox
+        .tf()
+        .args(["--kind", "class", "spi.c"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn kind_must_apply_to_the_extension() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().args(["--tb", "uart.c"]).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR uart.c: Kind 'tb' doesn't apply to C files.");
+}
+
+#[test]
+fn strict_rejects_illegal_identifiers() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args(["--strict", "my-uart.sv"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR my-uart.sv: 'my-uart' is not a legal identifier. Rename the file, e.g. to 'my_uart', or drop '--strict'.");
+    assert!(!sandbox.path("my-uart.sv").exists());
+}
+
+#[test]
+fn annotate_keeps_the_shebang_first() {
+    let sandbox = Sandbox::new();
+    sandbox.write("build.bash", "#!/bin/bash\nmake all\n");
+
+    sandbox
+        .tf()
+        .args(["annotate", "-a", "Jane Doe", "build.bash"])
+        .assert()
+        .success();
+
+    insta::assert_snapshot!(sandbox.read("build.bash"));
+}
+
+#[test]
+fn annotate_keeps_ruby_magic_comments_first() {
+    let sandbox = Sandbox::new();
+    sandbox.write(
+        "tool.rb",
+        "#!/usr/bin/env ruby\n# frozen_string_literal: true\nputs 1\n",
+    );
+
+    sandbox
+        .tf()
+        .args(["annotate", "-a", "Jane Doe", "tool.rb"])
+        .assert()
+        .success();
+
+    let annotated = sandbox.read("tool.rb");
+    assert!(annotated.starts_with("#!/usr/bin/env ruby\n# frozen_string_literal: true\n####"));
+}
+
+#[test]
+fn annotate_keeps_the_bom_first_and_the_line_endings() {
+    let sandbox = Sandbox::new();
+    sandbox.write("bom.bash", "\u{feff}#!/bin/bash\necho hi\n");
+    sandbox.write("run.bat", "@echo off\r\necho hi\r\n");
+
+    sandbox
+        .tf()
+        .args(["annotate", "-a", "Jane Doe", "bom.bash", "run.bat"])
+        .assert()
+        .success();
+
+    let script = sandbox.read("bom.bash");
+    assert!(script.starts_with("\u{feff}#!/bin/bash\n####"));
+    assert_eq!(script.matches('\u{feff}').count(), 1);
+    let batch = sandbox.read("run.bat");
+    assert!(batch.contains("Jane Doe"));
+    assert_eq!(batch.matches('\n').count(), batch.matches("\r\n").count());
+}
+
+#[test]
+fn annotate_keeps_the_module_docstring() {
+    let sandbox = Sandbox::new();
+    sandbox.write(
+        "tool.py",
+        "#!/usr/bin/env python3\n\"\"\"Old docstring.\"\"\"\nimport os\n",
+    );
+
+    sandbox
+        .tf()
+        .args(["annotate", "-a", "Jane Doe", "tool.py"])
+        .assert()
+        .success();
+    sandbox
+        .tf()
+        .args(["update", "-a", "New Name", "tool.py"])
+        .assert()
+        .success();
+
+    insta::assert_snapshot!(sandbox.read("tool.py"));
+}
+
+#[test]
+fn annotate_keeps_front_matter_first() {
+    let sandbox = Sandbox::new();
+    sandbox.write("post.md", "---\ntitle: Post\n---\n# Post\n");
+    sandbox.tf().args(["notes.md"]).assert().success();
+    let notes = sandbox.read("notes.md");
+
+    let output = sandbox
+        .tf()
+        .args(["annotate", "-a", "Jane Doe", "post.md", "notes.md"])
+        .output()
+        .unwrap();
+
+    insta::assert_snapshot!(common::stdout(&output), @r"
+    SKIPPED notes.md (already has a header)
+    ANNOTATED post.md
+    ");
+    assert!(sandbox
+        .read("post.md")
+        .starts_with("---\ntitle: Post\n---\n<!--\nAuthor  : Jane Doe\n"));
+    assert_eq!(sandbox.read("notes.md"), notes);
+}
+
+#[test]
+fn update_rewrites_only_the_metadata() {
+    let sandbox = Sandbox::new();
+    sandbox
+        .tf()
+        .args(["-a", "Old Name", "old.c"])
+        .assert()
+        .success();
+    let old = sandbox.read("old.c");
+    sandbox.write("new.c", &format!("{old}int x;\n"));
+
+    sandbox
+        .tf()
+        .args(["update", "-a", "New Name", "new.c"])
+        .assert()
+        .success();
+
+    insta::assert_snapshot!(sandbox.read("new.c"));
+}
+
+#[test]
+fn json_output_reports_files_errors_and_skipped_names() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args([
+            "--output", "json", "-a", "Jane Doe", "run.bash", "bad.xyz", "a.c",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let file = &report["files"][0];
+    assert_eq!(file["path"], "run.bash");
+    assert_eq!(file["filetype"], "bash");
+    assert_eq!(file["status"], "created");
+    assert!(file["absolute_path"]
+        .as_str()
+        .is_some_and(|path| std::path::Path::new(path).is_absolute()));
+    assert_eq!(report["errors"][0]["name"], "bad.xyz");
+    assert_eq!(report["skipped"], serde_json::json!(["a.c"]));
+}
+
+#[test]
+fn config_aliases_map_extensions_to_filetypes() {
+    let sandbox = Sandbox::new();
+
+    sandbox
+        .tf()
+        .args(["config", "set", "aliases", r#"{ cc = "cpp", v = "sv" }"#])
+        .assert()
+        .success();
+    let output = sandbox
+        .tf()
+        .args(["--dry-run", "--output", "json", "uart.cc", "alu.v"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = common::stdout(&output);
+    assert!(stdout.contains(r#""filetype": "cpp""#));
+    assert!(stdout.contains(r#""filetype": "sv""#));
+}
+
+#[test]
+fn interactive_mode_needs_a_terminal() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().arg("-i").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR: Interactive mode needs a terminal.");
+}
+
+#[test]
+fn date_format_is_configurable() {
+    let sandbox = Sandbox::new();
+
+    let iso = sandbox
+        .tf()
+        .args(["--stdout", "--date-format", "iso", "uart.c"])
+        .output()
+        .unwrap();
+    let custom = sandbox
+        .tf()
+        .args(["--stdout", "--date-format", "%d.%m.%Y", "uart.c"])
+        .output()
+        .unwrap();
+
+    assert!(common::stdout(&iso).contains("// Date    : 2025-01-01\n"));
+    assert!(common::stdout(&custom).contains("// Date    : 01.01.2025\n"));
+}
+
+#[test]
+fn copyright_years_come_from_the_date() {
+    let sandbox = Sandbox::new();
+
+    for (key, value) in [
+        ("copyright", "Acme Corp."),
+        ("copyright_since", "2019"),
+        ("copyright_notice", "All rights reserved."),
+    ] {
+        sandbox
+            .tf()
+            .args(["config", "set", key, value])
+            .assert()
+            .success();
+    }
+    let output = sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "--stdout", "run.bash"])
+        .output()
+        .unwrap();
+    let disabled = sandbox
+        .tf()
+        .args(["--stdout", "--no-copyright", "run.bash"])
+        .output()
+        .unwrap();
+
+    insta::assert_snapshot!(common::stdout(&output));
+    assert!(!common::stdout(&disabled).contains("Copyright"));
+}
+
+#[test]
+fn environment_variables_sit_between_config_and_flags() {
+    let sandbox = Sandbox::new();
+    sandbox.write("ci.toml", "date_format = \"iso\"\nguard_prefix = \"CI_\"\n");
+
+    let output = sandbox
+        .tf()
+        .env("TF_CONFIG", "ci.toml")
+        .env("TF_DATE_FORMAT", "%Y")
+        .args(["--stdout", "uart.h"])
+        .output()
+        .unwrap();
+    let flagged = sandbox
+        .tf()
+        .env("TF_CONFIG", "ci.toml")
+        .env("TF_DATE_FORMAT", "%Y")
+        .args(["--stdout", "--date-format", "us", "uart.h"])
+        .output()
+        .unwrap();
+    let invalid = sandbox
+        .tf()
+        .env("TF_GUARD_PATH", "maybe")
+        .arg("uart.h")
+        .output()
+        .unwrap();
+
+    assert!(common::stdout(&output).contains("// Date    : 2025\n"));
+    assert!(common::stdout(&output).contains("#ifndef CI_UART_H"));
+    assert!(common::stdout(&flagged).contains("// Date    : 01/01/2025\n"));
+    insta::assert_snapshot!(common::stderr(&invalid), @"ERROR reading config: $TF_GUARD_PATH: invalid type: string \"maybe\", expected a boolean");
+}
+
+#[test]
+fn a_missing_tf_config_fails() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .env("TF_CONFIG", "nope.toml")
+        .args(["--stdout", "uart.h"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(5));
+    assert!(common::stderr(&output).starts_with("ERROR reading config: nope.toml: "));
+}
+
+#[test]
+fn color_can_be_forced_on_redirected_output() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args(["--color", "always", "notes.xyz"])
+        .output()
+        .unwrap();
+
+    assert!(common::stderr(&output).starts_with("\u{1b}[31mERROR\u{1b}[0m notes.xyz"));
+}
+
+#[test]
+fn workflows_are_detected_by_their_directory() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args(["--dry-run", ".github/workflows/ci.yml", "Dockerfile"])
+        .output()
+        .unwrap();
+    let outside = sandbox.tf().args(["--dry-run", "ci.yml"]).output().unwrap();
+
+    insta::assert_snapshot!(common::stdout(&output), @r"
+    WOULD CREATE .github/workflows/ci.yml (GitHub Actions workflow, permissions: default)
+    WOULD CREATE Dockerfile (Dockerfile, permissions: default)
+    ");
+    insta::assert_snapshot!(common::stdout(&outside), @"WOULD CREATE ci.yml (YAML, permissions: default)");
+}
+
+#[test]
+fn manifests_are_generated_all_or_nothing() {
+    let sandbox = Sandbox::new();
+    sandbox.write(
+        "ip.toml",
+        r#"
+[vars]
+purpose = "UART block"
+
+[[file]]
+path = "rtl/uart.sv"
+
+[[file]]
+path = "tb/uart_tb.sv"
+kind = "tb"
+vars = { purpose = "UART testbench" }
+"#,
+    );
+    sandbox.write("tb/uart_tb.sv", "");
+
+    let output = sandbox
+        .tf()
+        .args(["--from-manifest", "ip.toml"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(!sandbox.path("rtl/uart.sv").exists());
+
+    fs::remove_file(sandbox.path("tb/uart_tb.sv")).unwrap();
+    sandbox
+        .tf()
+        .args(["--from-manifest", "ip.toml"])
+        .assert()
+        .success();
+
+    assert!(sandbox.read("rtl/uart.sv").contains("Purpose : UART block"));
+    assert!(sandbox
+        .read("tb/uart_tb.sv")
+        .contains("Purpose : UART testbench"));
+}
+
+#[test]
+fn large_manifests_are_reported_in_order() {
+    let sandbox = Sandbox::new();
+    let kinds = ["uvm-driver", "uvm-monitor", "uvm-sequence", "uvm-agent"];
+    let mut manifest = String::new();
+    let mut expected = String::new();
+    for agent in 0..50 {
+        for kind in kinds {
+            let path = format!(
+                "env/agent_{agent}/agent_{agent}_{}.sv",
+                kind.trim_start_matches("uvm-")
+            );
+            manifest.push_str(&format!(
+                "[[file]]\npath = \"{path}\"\nkind = \"{kind}\"\n\n"
+            ));
+            expected.push_str(&format!("CREATED {path}\n"));
+        }
+    }
+    sandbox.write("env.toml", &manifest);
+
+    let output = sandbox
+        .tf()
+        .args(["--from-manifest", "env.toml"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(common::stdout(&output), expected);
+    assert!(sandbox
+        .read("env/agent_49/agent_49_agent.sv")
+        .contains("class agent_49_agent extends uvm_agent;"));
+}
+
+#[test]
+fn manifest_errors_exit_with_their_code() {
+    let sandbox = Sandbox::new();
+    sandbox.write("empty.toml", "[vars]\nblock = \"uart\"\n");
+
+    let output = sandbox
+        .tf()
+        .args(["--from-manifest", "nope.toml"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(5));
+    assert!(common::stderr(&output).starts_with("ERROR nope.toml: "));
+
+    let output = sandbox
+        .tf()
+        .args(["--from-manifest", "empty.toml"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    insta::assert_snapshot!(common::stderr(&output), @"ERROR empty.toml: The manifest has no [[file]] entries.");
+}
+
+#[test]
+fn failed_writes_leave_nothing_behind() {
+    let sandbox = Sandbox::new();
+    sandbox.write(
+        "ip.toml",
+        r#"
+[[file]]
+path = "rtl/uart.sv"
+
+[[file]]
+path = "tb/uart_tb.sv"
+kind = "tb"
+"#,
+    );
+    // `tb` can't become a directory.
+    sandbox.write("tb", "");
+
+    let output = sandbox
+        .tf()
+        .args(["--from-manifest", "ip.toml"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(5));
+    assert!(common::stderr(&output).contains("creating 'tb/uart_tb.sv'"));
+    let mut left: Vec<String> = fs::read_dir(sandbox.project())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    left.sort();
+    assert_eq!(left, ["ip.toml", "tb"]);
+}
+
+#[test]
+fn failed_writes_keep_the_files_they_would_replace() {
+    let sandbox = Sandbox::new();
+    sandbox.write(
+        "ip.toml",
+        r#"
+[[file]]
+path = "uart.c"
+
+[[file]]
+path = "uart.h"
+"#,
+    );
+    sandbox.write("uart.c", "int x;\n");
+    fs::create_dir(sandbox.path("uart.h")).unwrap();
+
+    let output = sandbox
+        .tf()
+        .args(["--force", "--from-manifest", "ip.toml"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(5));
+    assert_eq!(sandbox.read("uart.c"), "int x;\n");
+    assert_eq!(fs::read_dir(sandbox.project()).unwrap().count(), 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn forced_writes_go_through_symlinks() {
+    let sandbox = Sandbox::new();
+    sandbox.write("real.c", "int x;\n");
+    std::os::unix::fs::symlink("real.c", sandbox.path("link.c")).unwrap();
+
+    sandbox.tf().args(["--force", "link.c"]).assert().success();
+
+    assert!(fs::symlink_metadata(sandbox.path("link.c"))
+        .unwrap()
+        .is_symlink());
+    assert!(sandbox.read("real.c").contains("File    : link.c"));
+}
+
+#[test]
+fn default_kinds_come_from_the_config() {
+    let sandbox = Sandbox::new();
+
+    sandbox
+        .tf()
+        .args(["config", "set", "default_kinds", r#"{ c = "lib" }"#])
+        .assert()
+        .success();
+    let lib = sandbox.tf().args(["--stdout", "uart.c"]).output().unwrap();
+    let main = sandbox
+        .tf()
+        .args(["--stdout", "--kind", "main", "uart.c"])
+        .output()
+        .unwrap();
+
+    assert!(common::stdout(&lib).contains("int uart_init(void) {"));
+    assert!(common::stdout(&main).contains("int main("));
+}
+
+#[test]
+fn filelist_gets_new_hdl_files_once() {
+    let sandbox = Sandbox::new();
+
+    for _ in 0..2 {
+        sandbox
+            .tf()
+            .args([
+                "--force",
+                "--filelist",
+                "sim/files.f",
+                "rtl/uart.sv",
+                "uart.c",
+            ])
+            .assert()
+            .success();
+    }
+
+    let filelist = sandbox.read("sim/files.f");
+    assert!(filelist.contains("File    : files.f"));
+    assert_eq!(filelist.matches("../rtl/uart.sv").count(), 1);
+    assert!(!filelist.contains("uart.c"));
+}
+
+#[cfg(unix)]
+#[test]
+fn permissions_come_from_the_config_and_flags() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let sandbox = Sandbox::new();
+    let mode = |name: &str| sandbox.path(name).metadata().unwrap().permissions().mode() & 0o777;
+
+    sandbox
+        .tf()
+        .args(["config", "set", "permissions", r#"{ py = "750" }"#])
+        .assert()
+        .success();
+    sandbox
+        .tf()
+        .args(["run.py", "check.py", "--kind", "script"])
+        .assert()
+        .success();
+    sandbox
+        .tf()
+        .args(["--no-executable", "run.bash"])
+        .assert()
+        .success();
+    sandbox
+        .tf()
+        .args(["--executable", "build.tcl"])
+        .assert()
+        .success();
+
+    assert_eq!(mode("run.py"), 0o750);
+    assert_eq!(mode("check.py"), 0o750);
+    assert_eq!(mode("run.bash"), 0o644);
+    assert_eq!(mode("build.tcl"), 0o744);
+}
+
+#[test]
+fn shebang_and_set_flags_can_be_overridden() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox
+        .tf()
+        .args([
+            "--stdout",
+            "--shebang",
+            "/usr/bin/env bash",
+            "--set-flags",
+            "eu",
+            "run.bash",
+        ])
+        .output()
+        .unwrap();
+
+    let script = common::stdout(&output);
+    assert!(script.starts_with("#!/usr/bin/env bash\n"));
+    assert!(script.contains("set -u"));
+    assert!(!script.contains("set -x"));
+}
+
+#[test]
+fn undo_deletes_the_last_run_unless_changed() {
+    let sandbox = Sandbox::new();
+
+    sandbox.tf().args(["uart.c", "run.bash"]).assert().success();
+    fs::write(sandbox.path("run.bash"), "edited\n").unwrap();
+    let refused = sandbox.tf().arg("undo").output().unwrap();
+
+    assert_eq!(refused.status.code(), Some(1));
+    assert!(common::stderr(&refused).contains("run.bash' changed since it was created."));
+    assert!(sandbox.path("uart.c").exists());
+
+    fs::remove_file(sandbox.path("run.bash")).unwrap();
+    let output = sandbox.tf().arg("undo").output().unwrap();
+
+    assert!(output.status.success());
+    insta::assert_snapshot!(common::stdout(&output), @r"
+    REMOVED uart.c
+    SKIPPED run.bash (already deleted)
+    ");
+    assert!(!sandbox.path("uart.c").exists());
+    sandbox.tf().arg("undo").assert().code(1);
+}
+
+#[test]
+fn undo_keeps_files_that_existed_before() {
+    let sandbox = Sandbox::new();
+    sandbox.write("keep.c", "code\n");
+    sandbox.write("empty.py", "");
+    sandbox.write("sim/files.f", "rtl/old.sv\n");
+
+    sandbox.tf().args(["--force", "keep.c"]).assert().success();
+    sandbox.tf().arg("undo").assert().code(1);
+    assert!(sandbox.path("keep.c").exists());
+
+    sandbox
+        .tf()
+        .args([
+            "--fill",
+            "--filelist",
+            "sim/files.f",
+            "empty.py",
+            "rtl/uart.sv",
+        ])
+        .assert()
+        .success();
+    let output = sandbox.tf().arg("undo").output().unwrap();
+
+    assert!(output.status.success());
+    insta::assert_snapshot!(common::stdout(&output), @r"
+    REMOVED rtl/uart.sv
+    RESTORED sim/files.f
+    ");
+    assert!(sandbox.path("empty.py").exists());
+    assert_eq!(sandbox.read("sim/files.f"), "rtl/old.sv\n");
+}
+
+#[test]
+fn editorconfig_sets_indentation_and_final_newline() {
+    let sandbox = Sandbox::new();
+    sandbox.write(
+        ".editorconfig",
+        "root = true\n\n[*]\nindent_style = space\nindent_size = 4\n\n[*.c]\ninsert_final_newline = false\n",
+    );
+
+    sandbox
+        .tf()
+        .args(["-a", "Jane Doe", "uart.c", "Makefile"])
+        .assert()
+        .success();
+    let ignored = sandbox
+        .tf()
+        .args(["--stdout", "--no-editorconfig", "spi.c"])
+        .output()
+        .unwrap();
+
+    insta::assert_snapshot!(sandbox.read("uart.c"));
+    assert!(sandbox.read("uart.c").ends_with('}'));
+    assert!(sandbox.read("Makefile").contains("\n\t$(CC)"));
+    assert!(common::stdout(&ignored).contains("\n  return 0;\n}\n"));
+}
+
+#[test]
+fn modelines_match_the_indentation() {
+    let sandbox = Sandbox::new();
+    sandbox.write("sub/.editorconfig", "[*.py]\nindent_size = 2\n");
+
+    let vim = sandbox
+        .tf()
+        .args(["--stdout", "--modeline", "vim", "main.go", "notes.md"])
+        .output()
+        .unwrap();
+    let emacs = sandbox
+        .tf()
+        .args(["--stdout", "--modeline", "emacs", "sub/tool.py"])
+        .output()
+        .unwrap();
+
+    let vim = common::stdout(&vim);
+    assert!(vim.contains("}\n\n// vim: ts=4 sw=4 noet\n"));
+    assert!(vim.contains("\n\n<!-- vim: set ts=4 sw=4 et: -->\n"));
+    assert!(common::stdout(&emacs).ends_with(
+        "\n\n# Local Variables:\n# indent-tabs-mode: nil\n# tab-width: 2\n# python-indent-offset: 2\n# End:\n"
+    ));
+}
+
+#[test]
+fn line_endings_and_bom_come_from_flags_editorconfig_and_config() {
+    let sandbox = Sandbox::new();
+    sandbox.write(
+        "win/.editorconfig",
+        "[*]\nend_of_line = lf\ncharset = utf-8-bom\n",
+    );
+
+    sandbox
+        .tf()
+        .args([
+            "config",
+            "set",
+            "filetype_line_endings",
+            r#"{ bat = "crlf" }"#,
+        ])
+        .assert()
+        .success();
+    sandbox
+        .tf()
+        .args(["build.bat", "run.bash", "win/setup.bat"])
+        .assert()
+        .success();
+    sandbox
+        .tf()
+        .args(["--line-endings", "crlf", "--no-bom", "win/run.ps1"])
+        .assert()
+        .success();
+
+    let bat = sandbox.read("build.bat");
+    assert!(bat.contains("\r\n") && !bat.replace("\r\n", "").contains('\n'));
+    assert!(!sandbox.read("run.bash").contains('\r'));
+    let setup = sandbox.read("win/setup.bat");
+    assert!(setup.starts_with('\u{feff}') && !setup.contains('\r'));
+    let ps1 = sandbox.read("win/run.ps1");
+    assert!(!ps1.starts_with('\u{feff}') && ps1.contains("\r\n"));
+}
+
+#[test]
+fn json_files_get_no_comments() {
+    let sandbox = Sandbox::new();
+
+    sandbox
+        .tf()
+        .args(["-a", "Jane \"JD\" Doe", "--modeline", "vim", "app.json"])
+        .assert()
+        .success();
+    let output = sandbox
+        .tf()
+        .args(["annotate", "app.json"])
+        .output()
+        .unwrap();
+
+    let contents = sandbox.read("app.json");
+    assert!(contents.starts_with("{\n"));
+    assert!(contents.ends_with("}\n"));
+    assert!(contents.contains(r#""author": "Jane \"JD\" Doe","#));
+    insta::assert_snapshot!(common::stdout(&output), @"SKIPPED app.json (the format has no comments)");
+}
+
+#[test]
+fn help_shows_examples_of_every_category() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().args(["new", "--help"]).output().unwrap();
+
+    let help = common::stdout(&output);
+    let examples = &help[help.find("Examples:").expect("an Examples section")..];
+    insta::assert_snapshot!(examples, @r"
+    Examples:
+      tf main.c                            # C
+      tf Makefile                          # Makefile
+      tf settings.toml                     # TOML
+      tf README.md                         # Markdown
+      tf uart.v                            # Verilog
+      tf uart_driver.sv --kind uvm-driver  # UVM (driver)
+    ");
+}
+
+#[test]
+fn man_pages_cover_every_command() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.tf().arg("generate-man").output().unwrap();
+    sandbox
+        .tf()
+        .args(["generate-man", "--out-dir", "man"])
+        .assert()
+        .success();
+
+    let page = common::stdout(&output);
+    assert!(page.contains(".TH tf 1 "));
+    assert!(page.contains("\\fB\\-k\\fR, \\fB\\-\\-keep\\-going\\fR\n"));
+    // Hyphens in the help text too, so options can be copied from it.
+    assert!(page.contains("(e.g. `tf bus_if.sv \\-\\-kind interface`)"));
+    assert!(page.contains(".SH EXAMPLES\n.nf\ntf main.c "));
+    let new = sandbox.read("man/tf-new.1");
+    assert!(new.contains(".SH SYNOPSIS\n\\fBtf new\\fR "));
+    assert!(sandbox.path("man/tf-undo.1").exists());
+    assert!(!sandbox.path("man/tf-help.1").exists());
+}
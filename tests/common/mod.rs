@@ -0,0 +1,89 @@
+//! Shared setup for the integration tests.
+// Every test crate compiles this module but uses only some of it.
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+/// 01/01/2025, so every header has the same date.
+const DATE: &str = "1735689600";
+
+/// A scratch directory with its own config and data directories, so tests
+/// never see the developer's settings, templates or git identity.
+pub struct Sandbox {
+    root: TempDir,
+}
+
+impl Sandbox {
+    pub fn new() -> Sandbox {
+        let root = tempfile::tempdir().expect("creating a temporary directory");
+        for dir in ["project", "home", "config", "data"] {
+            fs::create_dir(root.path().join(dir)).expect("creating the sandbox");
+        }
+        Sandbox { root }
+    }
+
+    /// Directory tf runs in. It's always called `project`, which keeps
+    /// `{{ dir }}` stable.
+    pub fn project(&self) -> PathBuf {
+        self.root.path().join("project")
+    }
+
+    /// The user template directory, `$XDG_CONFIG_HOME/tf/templates`.
+    pub fn template_dir(&self) -> PathBuf {
+        self.root.path().join("config").join("tf").join("templates")
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.project().join(name)
+    }
+
+    pub fn read(&self, name: &str) -> String {
+        fs::read_to_string(self.path(name)).expect("reading a generated file")
+    }
+
+    pub fn write(&self, name: &str, contents: &str) {
+        let path = self.path(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("creating a directory");
+        }
+        fs::write(path, contents).expect("writing a file");
+    }
+
+    /// `tf` running in [`Sandbox::project`] with a fixed date and no access
+    /// to the environment's configuration.
+    pub fn tf(&self) -> Command {
+        let root = self.root.path();
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("tf");
+        cmd.current_dir(self.project())
+            .env("SOURCE_DATE_EPOCH", DATE)
+            .env("NO_COLOR", "1")
+            .env("HOME", root.join("home"))
+            .env("XDG_CONFIG_HOME", root.join("config"))
+            .env("XDG_DATA_HOME", root.join("data"))
+            .env("GIT_CONFIG_GLOBAL", Path::new("/dev/null"))
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("USER", "tester")
+            .env_remove("VISUAL")
+            .env_remove("EDITOR");
+        // $TF_AUTHOR, $TF_CONFIG and the $TF_<KEY> config overrides.
+        for (key, _) in std::env::vars_os() {
+            if key.to_string_lossy().starts_with("TF_") {
+                cmd.env_remove(key);
+            }
+        }
+        cmd
+    }
+}
+
+pub fn stdout(output: &std::process::Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("stdout is UTF-8")
+}
+
+pub fn stderr(output: &std::process::Output) -> String {
+    String::from_utf8(output.stderr.clone()).expect("stderr is UTF-8")
+}
@@ -0,0 +1,172 @@
+//! Golden files for every built-in template, rendered by `tf list
+//! --preview` with its fixed sample values.
+
+mod common;
+
+use common::Sandbox;
+use std::{fs, path::Path};
+
+/// Names of the built-in templates, from `templates/*.tera`.
+fn builtin_templates() -> Vec<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .expect("reading templates/")
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            (path.extension()? == "tera").then_some(())?;
+            path.file_stem()?.to_str().map(String::from)
+        })
+        .filter(|name| name != "banner")
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn builtin_templates_match_golden_files() {
+    let sandbox = Sandbox::new();
+
+    for name in builtin_templates() {
+        let output = sandbox
+            .tf()
+            .args(["list", "--preview", &name])
+            .output()
+            .expect("running tf");
+        assert!(
+            output.status.success(),
+            "{name}: {}",
+            common::stderr(&output)
+        );
+        insta::assert_snapshot!(name, common::stdout(&output));
+    }
+}
+
+#[test]
+fn full_license_notice_follows_the_comment_style() {
+    let sandbox = Sandbox::new();
+
+    for file in ["uart.c", "uart.py", "uart.vhd", "run.bash"] {
+        let output = sandbox
+            .tf()
+            .args(["--stdout", "-a", "Jane Doe", "-l", "MIT"])
+            .args(["--license-style", "full", file])
+            .output()
+            .expect("running tf");
+        assert!(
+            output.status.success(),
+            "{file}: {}",
+            common::stderr(&output)
+        );
+        insta::assert_snapshot!(
+            format!("license_{}", file.replace('.', "_")),
+            common::stdout(&output)
+        );
+    }
+}
+
+#[test]
+fn user_templates_override_builtins() {
+    let sandbox = Sandbox::new();
+    fs::create_dir_all(sandbox.template_dir()).unwrap();
+    fs::write(
+        sandbox.template_dir().join("c.tera"),
+        "// {{ file }} by {{ author }} ({{ purpose }})\n",
+    )
+    .unwrap();
+
+    let output = sandbox
+        .tf()
+        .args([
+            "--stdout",
+            "-a",
+            "Jane Doe",
+            "--var",
+            "purpose=UART",
+            "uart.c",
+        ])
+        .output()
+        .expect("running tf");
+    assert_eq!(common::stdout(&output), "// uart.c by Jane Doe (UART)\n");
+}
+
+#[test]
+fn user_templates_share_partials() {
+    let sandbox = Sandbox::new();
+    let partials = sandbox.template_dir().join("partials");
+    fs::create_dir_all(&partials).unwrap();
+    fs::write(
+        partials.join("notice.tera"),
+        "{{ comment.line }}{{ file }} (c) ACME\n",
+    )
+    .unwrap();
+    fs::write(
+        partials.join("source.tera"),
+        "{% include \"partials/notice\" %}{% block body %}{% endblock %}",
+    )
+    .unwrap();
+    fs::write(
+        sandbox.template_dir().join("c.tera"),
+        "{% extends \"partials/source\" %}{% block body %}int x;\n{% endblock %}",
+    )
+    .unwrap();
+    fs::write(
+        sandbox.template_dir().join("bash.tera"),
+        "{% include \"partials/notice\" %}",
+    )
+    .unwrap();
+
+    let output = sandbox
+        .tf()
+        .args(["--stdout", "uart.c", "run.bash"])
+        .output()
+        .expect("running tf");
+    assert_eq!(
+        common::stdout(&output),
+        "// uart.c (c) ACME\nint x;\n# run.bash (c) ACME\n"
+    );
+}
+
+#[test]
+fn cpp_namespace_follows_the_standard() {
+    let sandbox = Sandbox::new();
+
+    for (std, name) in [("c++17", "uart.hpp"), ("c++14", "Uart.cpp")] {
+        let output = sandbox
+            .tf()
+            .args([
+                "--stdout",
+                "-a",
+                "Jane Doe",
+                "--namespace",
+                "acme::hw",
+                "--std",
+                std,
+                "--class",
+                name,
+            ])
+            .output()
+            .expect("running tf");
+        insta::assert_snapshot!(
+            format!("namespace_{}", name.replace('.', "_")),
+            common::stdout(&output)
+        );
+    }
+}
+
+#[test]
+fn python_tests_name_their_fixture_after_the_subject() {
+    let sandbox = Sandbox::new();
+
+    for (file, fixture) in [("test_uart.py", "uart"), ("test_.py", "test_")] {
+        let output = sandbox
+            .tf()
+            .args(["--stdout", "-a", "Jane Doe", file])
+            .output()
+            .expect("running tf");
+        assert!(
+            common::stdout(&output).contains(&format!("def {fixture}():\n")),
+            "{file}: {}",
+            common::stdout(&output)
+        );
+    }
+}